@@ -1,17 +1,43 @@
-use crate::tools::{self, InstallMethod, Tool};
+use crate::{
+    archive::Archive,
+    cache,
+    doctor::{self, Status},
+    history::{self, Action, CommandRun},
+    lockfile::{self, LockedTool, Lockfile},
+    paths,
+    plan::{Plan, Step},
+    project::ProjectManifest,
+    settings::Settings,
+    shell,
+    tools::{self, Channel, InstallMethod, Tool, ToolVersion},
+    trust::{self, TrustStore, Verdict},
+    version::{Version, VersionReq},
+    versions::{
+        CacheMode, UpdateStatus, check_latest_versions, get_npm_dist_tag, get_npm_matching,
+        update_status,
+    },
+};
 use anyhow::{Context, Result};
 use colored::*;
 use inquire::MultiSelect;
 use std::{
-    fs, io,
+    collections::BTreeSet,
+    fs,
+    io::{self, IsTerminal, Write},
     path::{Path, PathBuf},
-    process::Command,
+    process::{Command, ExitStatus, Stdio},
+    time::Instant,
 };
 
-pub async fn handle_install_command(tool_name: Option<&str>) -> Result<()> {
-    let tools = tools::catalog();
+pub async fn handle_install_command(
+    tool_name: Option<&str>,
+    dry_run: bool,
+    review: bool,
+) -> Result<()> {
+    let tools = tools::catalog()?;
 
-    if let Some(name) = tool_name {
+    if let Some(spec) = tool_name {
+        let (name, version) = parse_tool_spec(spec);
         let tool = find_tool(&tools, name).with_context(|| {
             format!(
                 "Tool '{}' not found. Available tools: {}",
@@ -20,12 +46,31 @@ pub async fn handle_install_command(tool_name: Option<&str>) -> Result<()> {
             )
         })?;
 
+        if let Some(version) = version {
+            if is_installed_at(tool, version) {
+                println!(
+                    "{} {} {} is already installed!",
+                    "✓".green(),
+                    tool.name,
+                    version
+                );
+                return Ok(());
+            }
+            // Replace the copy that is there rather than adding one through another method.
+            let method = if tool.is_installed() {
+                tool.installed_method()
+            } else {
+                tool.install_method().clone()
+            };
+            return install_version(tool, &method, version, None, dry_run, review).await;
+        }
+
         if tool.is_installed() {
             println!("{} {} is already installed!", "✓".green(), tool.name);
             return Ok(());
         }
 
-        install_tool(tool).await?;
+        install_tool(tool, dry_run, review).await?;
         return Ok(());
     }
 
@@ -47,10 +92,11 @@ pub async fn handle_install_command(tool_name: Option<&str>) -> Result<()> {
             format!(
                 "{} ({})",
                 t.name,
-                match &t.install_method {
+                match t.install_method() {
                     InstallMethod::Npm(pkg) => format!("npm: {}", pkg),
                     InstallMethod::Bootstrap(_) => "bootstrap".to_string(),
                     InstallMethod::Brew(formula) => format!("brew: {}", formula),
+                    InstallMethod::BrewCask(cask) => format!("brew cask: {}", cask),
                     InstallMethod::Amp(_) => "amp installer".to_string(),
                 }
             )
@@ -77,10 +123,9 @@ pub async fn handle_install_command(tool_name: Option<&str>) -> Result<()> {
                 if let Some(tool) = uninstalled_tools
                     .iter()
                     .find(|t| selection.starts_with(&t.name))
+                    && let Err(e) = install_tool(tool, dry_run, review).await
                 {
-                    if let Err(e) = install_tool(tool).await {
-                        println!("{} Failed to install {}: {}", "✗".red(), tool.name, e);
-                    }
+                    println!("{} Failed to install {}: {}", "✗".red(), tool.name, e);
                 }
             }
 
@@ -93,12 +138,101 @@ pub async fn handle_install_command(tool_name: Option<&str>) -> Result<()> {
     Ok(())
 }
 
+/// Moves the files of a tool's most recent uninstall back, or lists archives without a tool.
+pub fn handle_restore_command(tool_name: Option<&str>) -> Result<()> {
+    purge_archives();
+    let Some(name) = tool_name else {
+        let archives = Archive::list(None)?;
+        if archives.is_empty() {
+            println!("{}", "No uninstall archives.".yellow());
+        }
+        for archive in archives {
+            println!(
+                "{} {} ago: {}",
+                archive.tool().bold(),
+                cache::format_age(archive.age()),
+                archive.dir().display().to_string().bright_black()
+            );
+            for entry in archive.entries() {
+                println!("  - {}", entry.original.display());
+            }
+        }
+        return Ok(());
+    };
+
+    let tools = tools::catalog()?;
+    let identifier = find_tool(&tools, name).map_or(name, |tool| tool.identifier.as_str());
+    let archive = Archive::list(Some(identifier))?
+        .into_iter()
+        .next()
+        .with_context(|| format!("No uninstall archive for '{}'", name))?;
+
+    println!(
+        "Restoring {} from {} ago...",
+        identifier.bright_cyan(),
+        cache::format_age(archive.age())
+    );
+    let result = archive.restore()?;
+    for path in &result.restored {
+        println!("{} {}", "✓".green(), path.display());
+    }
+    let local_bin = shell::local_bin()?;
+    if result
+        .restored
+        .iter()
+        .any(|path| path.parent() == Some(&local_bin))
+    {
+        put_local_bin_on_path();
+    }
+    for path in &result.conflicts {
+        println!(
+            "{} {} already exists; left in the archive",
+            "!".yellow(),
+            path.display()
+        );
+    }
+    if !result.conflicts.is_empty() {
+        anyhow::bail!(
+            "{} of {} paths could not be restored",
+            result.conflicts.len(),
+            result.restored.len() + result.conflicts.len()
+        );
+    }
+    Ok(())
+}
+
+/// Deletes uninstall archives older than the retention setting. Failures only warn.
+fn purge_archives() {
+    let result = Settings::load().and_then(|settings| match settings.archive_retention() {
+        Some(retention) => Archive::purge(retention),
+        None => Ok(0),
+    });
+    match result {
+        Ok(0) => {}
+        Ok(purged) => println!(
+            "{} Purged {} expired uninstall {}",
+            "→".cyan(),
+            purged,
+            if purged == 1 { "archive" } else { "archives" }
+        ),
+        Err(e) => println!(
+            "{} Could not purge uninstall archives: {:#}",
+            "!".yellow(),
+            e
+        ),
+    }
+}
+
 pub async fn handle_uninstall_command(
     tool_name: Option<&str>,
     remove_config: bool,
     force: bool,
+    dry_run: bool,
 ) -> Result<()> {
-    let tools = tools::catalog();
+    if !dry_run {
+        purge_archives();
+    }
+    let tools = tools::catalog()?;
 
     if let Some(name) = tool_name {
         let tool = find_tool(&tools, name).with_context(|| {
@@ -114,7 +248,7 @@ pub async fn handle_uninstall_command(
             return Ok(());
         }
 
-        uninstall_tool(tool, remove_config, force).await?;
+        uninstall_tool(tool, remove_config, force, dry_run).await?;
         return Ok(());
     }
 
@@ -140,10 +274,10 @@ pub async fn handle_uninstall_command(
             println!("\n{}", "Starting uninstallation...".bright_cyan());
 
             for selection in selections {
-                if let Some(tool) = installed_tools.iter().find(|t| t.name == selection) {
-                    if let Err(e) = uninstall_tool(tool, remove_config, force).await {
-                        println!("{} Failed to uninstall {}: {}", "✗".red(), tool.name, e);
-                    }
+                if let Some(tool) = installed_tools.iter().find(|t| t.name == selection)
+                    && let Err(e) = uninstall_tool(tool, remove_config, force, dry_run).await
+                {
+                    println!("{} Failed to uninstall {}: {}", "✗".red(), tool.name, e);
                 }
             }
 
@@ -156,16 +290,192 @@ pub async fn handle_uninstall_command(
     Ok(())
 }
 
-pub async fn handle_upgrade_command(tool_name: Option<&str>) -> Result<()> {
-    let tools = tools::catalog();
+/// Prints logged installs, upgrades and uninstalls, newest first.
+pub fn handle_history_command(tool_name: Option<&str>) -> Result<()> {
+    let tools = tools::catalog()?;
+    // Tools removed from the catalog can still be looked up by identifier.
+    let identifier =
+        tool_name.map(|name| find_tool(&tools, name).map_or(name, |tool| tool.identifier.as_str()));
+    let entries = history::load(identifier)?;
+    if entries.is_empty() {
+        println!("{}", "No history yet.".yellow());
+        return Ok(());
+    }
 
-    let Some(name) = tool_name else {
+    for entry in entries.iter().rev() {
+        let name = find_tool(&tools, &entry.tool).map_or(entry.tool.as_str(), |tool| &tool.name);
+        let versions = match (&entry.version_before, &entry.version_after) {
+            (Some(before), Some(after)) if before != after => format!("{} → {}", before, after),
+            (Some(version), Some(_)) => version.clone(),
+            (Some(before), None) => format!("{} → none", before),
+            (None, Some(after)) => format!("none → {}", after),
+            (None, None) => "unknown version".to_string(),
+        };
         println!(
-            "{} Specify a tool to upgrade, e.g., `ai-cli-apps upgrade amp`.",
-            "!".yellow()
+            "{} {} ago: {} {} {} ({}, {:.1}s)",
+            if entry.success {
+                "✓".green()
+            } else {
+                "✗".red()
+            },
+            cache::format_age(entry.age()),
+            entry.action,
+            name.bold(),
+            versions,
+            entry.method,
+            entry.duration_ms as f64 / 1000.0
         );
+        for command in &entry.commands {
+            let exit = match command.exit_code {
+                Some(0) => String::new(),
+                Some(code) => format!(" (exit {})", code),
+                None => " (did not finish)".to_string(),
+            };
+            println!("  $ {}{}", command.command, exit.red());
+        }
+        if let Some(error) = &entry.error {
+            println!("  {}", error.red());
+        }
+    }
+    Ok(())
+}
+
+/// Removes every copy of a tool but one, each through the uninstall path of its own origin.
+pub async fn handle_dedupe_command(tool_name: &str, keep: Option<&str>, force: bool) -> Result<()> {
+    let tools = tools::catalog()?;
+    let tool = find_tool(&tools, tool_name).with_context(|| {
+        format!(
+            "Tool '{}' not found. Available tools: {}",
+            tool_name,
+            format_available_tools(&tools)
+        )
+    })?;
+
+    let copies = tool.installations();
+    if copies.len() < 2 {
+        println!("{} {} is installed at most once", "✓".green(), tool.name);
         return Ok(());
+    }
+
+    let methods: Vec<Option<InstallMethod>> = copies
+        .iter()
+        .map(|copy| copy.install_method(tool))
+        .collect();
+    let kept = kept_copy(tool, &methods, keep)?;
+
+    println!(
+        "{} Keeping {} {}",
+        "→".cyan(),
+        copies[kept].path.display(),
+        copies[kept].version.as_deref().unwrap_or("?").bright_blue()
+    );
+    println!("{} Removing:", "→".cyan());
+    for (index, copy) in copies.iter().enumerate() {
+        if index != kept {
+            println!(
+                "  - {} {}",
+                copy.path.display(),
+                copy.version.as_deref().unwrap_or("?").bright_blue()
+            );
+        }
+    }
+    if !force {
+        println!("{} Remove these copies? [y/N]", "?".yellow());
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("{} Keeping every copy", "→".cyan());
+            return Ok(());
+        }
+    }
+
+    let mut failures = 0;
+    for (index, (copy, method)) in copies.iter().zip(&methods).enumerate() {
+        if index == kept {
+            continue;
+        }
+        let result = match removal_method(method.as_ref(), methods[kept].as_ref()) {
+            Ok(method) => {
+                println!("\nRemoving {} ({})...", copy.path.display(), method.kind());
+                remove_with(tool, method, false, false).await
+            }
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) if copy.path.exists() => {
+                println!("{} {} is still there", "!".yellow(), copy.path.display());
+                failures += 1;
+            }
+            Ok(()) => {}
+            Err(e) => {
+                println!("{} {}: {:#}", "✗".red(), copy.path.display(), e);
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        anyhow::bail!(
+            "{} of {} extra copies could not be removed",
+            failures,
+            copies.len() - 1
+        );
+    }
+    println!("\n{} {} is installed once", "✓".green(), tool.name);
+    Ok(())
+}
+
+/// Index of the copy `dedupe` keeps: the first one installed with `keep`, else the one on PATH.
+fn kept_copy(tool: &Tool, methods: &[Option<InstallMethod>], keep: Option<&str>) -> Result<usize> {
+    let Some(kind) = keep else {
+        return Ok(0);
     };
+    methods
+        .iter()
+        .position(|method| method.as_ref().is_some_and(|m| m.kind() == kind))
+        .with_context(|| {
+            let found: Vec<&str> = methods.iter().flatten().map(|m| m.kind()).collect();
+            format!(
+                "No copy of {} was installed with {}. Installed with: {}",
+                tool.name,
+                kind,
+                found.join(", ")
+            )
+        })
+}
+
+/// How an extra copy can be removed without touching the kept one.
+fn removal_method<'a>(
+    method: Option<&'a InstallMethod>,
+    kept: Option<&InstallMethod>,
+) -> Result<&'a InstallMethod> {
+    match (method, kept) {
+        (None, _) => anyhow::bail!("not installed by a known method; remove it manually"),
+        // Removing through the same method would take the kept copy with it.
+        (Some(method), Some(kept))
+            if method.kind() == kept.kind() && method.source() == kept.source() =>
+        {
+            anyhow::bail!(
+                "installed with the same {} as the kept copy; remove it manually",
+                method.kind()
+            )
+        }
+        (Some(method), _) => Ok(method),
+    }
+}
+
+pub async fn handle_upgrade_command(
+    tool_name: Option<&str>,
+    all: bool,
+    dry_run: bool,
+    review: bool,
+) -> Result<()> {
+    let tools = tools::catalog()?;
+
+    let Some(spec) = tool_name else {
+        return upgrade_outdated(&tools, all, dry_run, review).await;
+    };
+    let (name, version) = parse_tool_spec(spec);
 
     let tool = find_tool(&tools, name).with_context(|| {
         format!(
@@ -185,421 +495,2073 @@ pub async fn handle_upgrade_command(tool_name: Option<&str>) -> Result<()> {
         return Ok(());
     }
 
-    upgrade_tool(tool).await
+    match version {
+        Some(version) => {
+            install_version(
+                tool,
+                &tool.installed_method(),
+                version,
+                None,
+                dry_run,
+                review,
+            )
+            .await
+        }
+        None => upgrade_tool(tool, dry_run, review).await,
+    }
 }
 
-async fn install_tool(tool: &Tool) -> Result<()> {
-    println!("Installing {}...", tool.name.bright_cyan());
+/// Upgrades outdated tools that are not held, picked interactively unless `all` is set.
+async fn upgrade_outdated(tools: &[Tool], all: bool, dry_run: bool, review: bool) -> Result<()> {
+    let held = Settings::load()?.held;
 
-    match &tool.install_method {
-        InstallMethod::Bootstrap(url) => {
-            run_install_script(url, "bootstrap.sh", "bootstrap script").await?;
-            println!("{} {} installed successfully!", "✓".green(), tool.name);
-        }
-        InstallMethod::Amp(url) => {
-            run_install_script(url, "amp_install.sh", "Amp installer").await?;
-            println!("{} {} installed successfully!", "✓".green(), tool.name);
-        }
-        InstallMethod::Brew(formula) => {
-            let status = Command::new("brew")
-                .args(["install", formula])
-                .status()
-                .context("Failed to run brew install")?;
+    let mut versions: Vec<ToolVersion> = tools
+        .iter()
+        .map(Tool::installed_version)
+        .filter(|v| v.installed.is_some())
+        .collect();
+    if versions.is_empty() {
+        println!("{}", "No tools are currently installed.".yellow());
+        return Ok(());
+    }
+    let mode = if dry_run {
+        CacheMode::ReadOnly
+    } else {
+        CacheMode::Refresh
+    };
+    check_latest_versions(&mut versions, true, mode).await;
 
-            if status.success() {
-                println!("{} {} installed successfully!", "✓".green(), tool.name);
-            } else {
-                anyhow::bail!("brew install failed for {}", tool.name);
+    let describe = |version: &ToolVersion| {
+        format!(
+            "{} {} → {}",
+            version.name,
+            version
+                .installed
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+            version.latest.as_deref().unwrap_or_default()
+        )
+    };
+
+    let (upgradable, held) = split_held(tools, &versions, &held);
+    for version in held {
+        println!(
+            "{} {} (held)",
+            "!".yellow(),
+            describe(version).bright_black()
+        );
+    }
+    let outdated: Vec<(&Tool, String)> = upgradable
+        .into_iter()
+        .map(|(tool, version)| (tool, describe(version)))
+        .collect();
+
+    if outdated.is_empty() {
+        println!("{}", "All tools are up to date! ✓".green());
+        return Ok(());
+    }
+
+    let selected: Vec<&Tool> = if all {
+        outdated.iter().map(|(tool, _)| *tool).collect()
+    } else {
+        println!("{}", "\nSelect tools to upgrade:".bright_cyan().bold());
+        let options: Vec<String> = outdated.iter().map(|(_, label)| label.clone()).collect();
+        let selections = match MultiSelect::new("Tools:", options)
+            .with_help_message("↑↓ to move, space to select, enter to confirm")
+            .prompt()
+        {
+            Ok(selections) => selections,
+            Err(e) => {
+                println!("{} Selection cancelled: {}", "✗".red(), e);
+                return Ok(());
             }
+        };
+        outdated
+            .iter()
+            .filter(|(_, label)| selections.contains(label))
+            .map(|(tool, _)| *tool)
+            .collect()
+    };
+
+    if selected.is_empty() {
+        println!("{}", "No tools selected.".yellow());
+        return Ok(());
+    }
+
+    println!("\n{}", "Starting upgrade...".bright_cyan());
+    for tool in selected {
+        if let Err(e) = upgrade_tool(tool, dry_run, review).await {
+            println!("{} Failed to upgrade {}: {}", "✗".red(), tool.name, e);
         }
-        InstallMethod::Npm(package) => {
-            let status = Command::new("npm")
-                .args(["install", "-g", package])
-                .status()
-                .context("Failed to run npm install")?;
+    }
+    println!("\n{}", "Upgrade complete!".green().bold());
+    Ok(())
+}
 
-            if status.success() {
-                println!("{} {} installed successfully!", "✓".green(), tool.name);
-            } else {
-                anyhow::bail!("npm install failed for {}", tool.name);
-            }
+/// Outdated tools split into the ones to upgrade and the ones the hold list keeps back.
+fn split_held<'a>(
+    tools: &'a [Tool],
+    versions: &'a [ToolVersion],
+    held: &BTreeSet<String>,
+) -> (Vec<(&'a Tool, &'a ToolVersion)>, Vec<&'a ToolVersion>) {
+    let mut upgradable = Vec::new();
+    let mut kept_back = Vec::new();
+    for version in versions {
+        if update_status(version) != UpdateStatus::Outdated {
+            continue;
+        }
+        let tool = tools
+            .iter()
+            .find(|t| Some(&t.identifier) == version.identifier.as_ref())
+            .expect("versions come from the catalog");
+        if held.contains(&tool.identifier) {
+            kept_back.push(version);
+        } else {
+            upgradable.push((tool, version));
         }
     }
+    (upgradable, kept_back)
+}
+
+/// Lists held tools, or adds a tool to (or removes it from) the hold list.
+pub fn handle_hold_command(tool_name: Option<&str>, release: bool) -> Result<()> {
+    let tools = tools::catalog()?;
+    let mut settings = Settings::load()?;
+
+    let Some(name) = tool_name else {
+        if settings.held.is_empty() {
+            println!("{}", "No tools are held.".bright_black());
+        }
+        for identifier in &settings.held {
+            let name = find_tool(&tools, identifier).map_or(identifier.as_str(), |t| &t.name);
+            println!("{} {} ({})", "!".yellow(), name, identifier.bright_black());
+        }
+        return Ok(());
+    };
+
+    let tool = find_tool(&tools, name).with_context(|| {
+        format!(
+            "Tool '{}' not found. Available tools: {}",
+            name,
+            format_available_tools(&tools)
+        )
+    })?;
 
+    if release {
+        settings.held.remove(&tool.identifier);
+        settings.save()?;
+        println!("{} {} is no longer held", "✓".green(), tool.name);
+    } else {
+        settings.held.insert(tool.identifier.clone());
+        settings.save()?;
+        println!(
+            "{} {} is held; bulk upgrades will skip it",
+            "✓".green(),
+            tool.name
+        );
+    }
     Ok(())
 }
 
-async fn uninstall_tool(tool: &Tool, remove_config: bool, force: bool) -> Result<()> {
-    println!("Uninstalling {}...", tool.name.bright_cyan());
+/// Shows the channel of every tool, shows one tool's channel, or switches it.
+pub fn handle_channel_command(tool_name: Option<&str>, channel: Option<Channel>) -> Result<()> {
+    let tools = tools::catalog()?;
 
-    match &tool.install_method {
-        InstallMethod::Bootstrap(_) => {
-            let home = std::env::var("HOME").context("HOME environment variable not set")?;
-            let binary_name = tool.binary_name.as_deref().unwrap_or(tool.name.as_str());
-
-            let symlink_path = Path::new(&home)
-                .join(".local")
-                .join("bin")
-                .join(binary_name);
-
-            let versions_path = Path::new(&home)
-                .join(".local")
-                .join("share")
-                .join(binary_name)
-                .join("versions");
-            let config_dirs: Vec<_> = if tool.config_dirs.is_empty() {
-                vec![Path::new(&home).join(format!(".{}", binary_name))]
-            } else {
-                tool.config_dirs
-                    .iter()
-                    .map(|dir| Path::new(&home).join(dir))
-                    .collect()
-            };
-            let mut existing_configs: Vec<_> = config_dirs
-                .into_iter()
-                .filter(|path| path.exists())
-                .collect();
-
-            let mut removed_items = Vec::new();
-            let mut binary_paths = vec![symlink_path];
-            binary_paths.extend(
-                tool.extra_binary_paths
-                    .iter()
-                    .map(|extra| Path::new(&home).join(extra)),
+    let Some(name) = tool_name else {
+        for tool in &tools {
+            println!(
+                "{} {} {}",
+                format!("{}:", tool.name).bold(),
+                tool.channel.to_string().bright_blue(),
+                format_channels(&tool.supported_channels()).bright_black()
             );
+        }
+        return Ok(());
+    };
 
-            for binary_path in binary_paths {
-                if binary_path.exists() {
-                    fs::remove_file(&binary_path).with_context(|| {
-                        format!("Failed to remove binary {}", binary_path.display())
-                    })?;
-                    removed_items.push(format!("binary: {}", binary_path.display()));
-                }
-            }
+    let tool = find_tool(&tools, name).with_context(|| {
+        format!(
+            "Tool '{}' not found. Available tools: {}",
+            name,
+            format_available_tools(&tools)
+        )
+    })?;
 
-            if versions_path.exists() {
-                if let Some(parent) = versions_path.parent() {
-                    fs::remove_dir_all(parent).context("Failed to remove versions directory")?;
-                    removed_items.push(format!("versions: {}", parent.display()));
-                }
-            }
+    let Some(channel) = channel else {
+        println!(
+            "{} follows the {} channel {}",
+            tool.name,
+            tool.channel.to_string().bright_blue(),
+            format_channels(&tool.supported_channels()).bright_black()
+        );
+        return Ok(());
+    };
 
-            if !existing_configs.is_empty() {
-                if existing_configs.len() == 1 {
-                    println!(
-                        "{} Config directory found at: {}",
-                        "→".cyan(),
-                        existing_configs[0].display()
-                    );
-                } else {
-                    println!("{} Config directories found:", "→".cyan());
-                    for path in &existing_configs {
-                        println!("  - {}", path.display());
-                    }
-                }
+    if !tool.supported_channels().contains(&channel) {
+        anyhow::bail!(
+            "{} does not support the '{}' channel. Supported channels: {}",
+            tool.name,
+            channel,
+            format_channels(&tool.supported_channels())
+        );
+    }
 
-                if remove_config {
-                    let should_remove = if force {
-                        true
-                    } else {
-                        println!(
-                            "{} Remove config directories? (contains settings and history) [y/N]",
-                            "?".yellow()
-                        );
-                        let mut input = String::new();
-                        io::stdin().read_line(&mut input)?;
-                        input.trim().eq_ignore_ascii_case("y")
-                    };
-
-                    if should_remove {
-                        for path in existing_configs.drain(..) {
-                            fs::remove_dir_all(&path).with_context(|| {
-                                format!("Failed to remove config directory {}", path.display())
-                            })?;
-                            removed_items.push(format!("config: {}", path.display()));
-                        }
-                    } else {
-                        println!("{} Keeping config directories", "→".cyan());
-                    }
-                } else {
-                    let suffix = if existing_configs.len() > 1 {
-                        "directories"
-                    } else {
-                        "directory"
-                    };
-                    println!(
-                        "{} Keeping config {} (use --remove-config to remove it)",
-                        "→".cyan(),
-                        suffix
-                    );
-                }
-            }
+    let mut settings = Settings::load()?;
+    settings.channels.insert(tool.identifier.clone(), channel);
+    settings.save()?;
+
+    println!(
+        "{} {} now follows the {} channel",
+        "✓".green(),
+        tool.name,
+        channel.to_string().bright_blue()
+    );
+    if tool.is_installed() {
+        println!(
+            "{} Run `ai-cli-apps upgrade {}` to switch the installed version.",
+            "→".cyan(),
+            tool.identifier
+        );
+    }
+    Ok(())
+}
 
-            if removed_items.is_empty() {
-                println!("{} {} not found on system", "!".yellow(), tool.name);
-            } else {
-                println!("{} {} uninstalled successfully!", "✓".green(), tool.name);
-                println!("{} Removed:", "→".cyan());
-                for item in removed_items {
-                    println!("  - {}", item);
-                }
-            }
+/// Reports whether the prerequisites of every install method are met, applying the safe fixes
+/// when `fix` is set.
+pub fn handle_doctor_command(fix: bool) -> Result<()> {
+    let checks = doctor::run();
+    let width = checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+
+    let mut failures = 0;
+    let mut fixable = 0;
+    for check in &checks {
+        let symbol = match check.status {
+            Status::Pass => "✓".green(),
+            Status::Warn => "!".yellow(),
+            Status::Fail => "✗".red(),
+        };
+        println!(
+            "{} {:width$} {}",
+            symbol,
+            check.name,
+            check.detail.bright_black(),
+            width = width
+        );
+        if check.status == Status::Pass {
+            continue;
+        }
+        if let Some(hint) = &check.hint {
+            println!("  {} {}", "→".cyan(), hint);
         }
-        InstallMethod::Amp(_) => {
-            let home = std::env::var("HOME")
-                .or_else(|_| std::env::var("USERPROFILE"))
-                .context("HOME environment variable not set")?;
-            let home_path = Path::new(&home);
-            let amp_home = home_path.join(".amp");
-            let local_bin = home_path.join(".local").join("bin");
-            let mut removed_items = Vec::new();
-
-            for shim in ["amp", "amp.bat"] {
-                let shim_path = local_bin.join(shim);
-                if shim_path.exists() {
-                    fs::remove_file(&shim_path)
-                        .with_context(|| format!("Failed to remove {}", shim_path.display()))?;
-                    removed_items.push(format!("shim: {}", shim_path.display()));
-                }
-            }
-
-            if amp_home.exists() {
-                fs::remove_dir_all(&amp_home).context("Failed to remove AMP_HOME directory")?;
-                removed_items.push(format!("AMP_HOME: {}", amp_home.display()));
-            }
 
-            let config_home = std::env::var("XDG_CONFIG_HOME")
-                .map(PathBuf::from)
-                .unwrap_or_else(|_| home_path.join(".config"));
-            let data_home = std::env::var("XDG_DATA_HOME")
-                .map(PathBuf::from)
-                .unwrap_or_else(|_| home_path.join(".local").join("share"));
-            let cache_home = std::env::var("XDG_CACHE_HOME")
-                .map(PathBuf::from)
-                .unwrap_or_else(|_| home_path.join(".cache"));
-
-            if remove_config {
-                let should_remove = if force {
-                    true
-                } else {
-                    println!(
-                        "{} Remove Amp config/cache directories? [y/N]",
-                        "?".yellow()
-                    );
-                    let mut input = String::new();
-                    io::stdin().read_line(&mut input)?;
-                    input.trim().eq_ignore_ascii_case("y")
-                };
-
-                if should_remove {
-                    for path in [
-                        config_home.join("amp"),
-                        data_home.join("amp"),
-                        cache_home.join("amp"),
-                    ] {
-                        if path.exists() {
-                            let metadata = fs::metadata(&path)?;
-                            if metadata.is_file() {
-                                fs::remove_file(&path).with_context(|| {
-                                    format!("Failed to remove {}", path.display())
-                                })?;
-                            } else {
-                                fs::remove_dir_all(&path).with_context(|| {
-                                    format!("Failed to remove {}", path.display())
-                                })?;
-                            }
-                            removed_items.push(format!("config/data/cache: {}", path.display()));
-                        }
+        let mut resolved = false;
+        if let Some(action) = &check.fix {
+            if fix {
+                match action.apply() {
+                    Ok(()) => {
+                        println!("  {} Fixed: {}", "✓".green(), action);
+                        resolved = true;
                     }
-                } else {
-                    println!("{} Keeping Amp config/cache directories", "→".cyan());
+                    Err(e) => println!("  {} Could not {}: {:#}", "✗".red(), action, e),
                 }
             } else {
-                println!(
-                    "{} Keeping Amp config/cache directories (use --remove-config to delete them)",
-                    "→".cyan()
-                );
-            }
-
-            if removed_items.is_empty() {
-                println!("{} Amp files not found on system", "!".yellow());
-            } else {
-                println!("{} {} uninstalled successfully!", "✓".green(), tool.name);
-                println!("{} Removed:", "→".cyan());
-                for item in removed_items {
-                    println!("  - {}", item);
-                }
-                println!(
-                    "{} Remove any PATH entries for ~/.local/bin/amp in your shell rc files.",
-                    "→".cyan()
-                );
+                fixable += 1;
             }
         }
-        InstallMethod::Npm(package) => {
-            let status = Command::new("npm")
-                .args(["uninstall", "-g", package])
-                .status()
-                .context("Failed to run npm uninstall")?;
-
-            if status.success() {
-                println!("{} {} uninstalled successfully!", "✓".green(), tool.name);
-            } else {
-                anyhow::bail!("npm uninstall failed for {}", tool.name);
-            }
+        if check.status == Status::Fail && !resolved {
+            failures += 1;
         }
-        InstallMethod::Brew(formula) => {
-            let status = Command::new("brew")
-                .args(["uninstall", formula])
-                .status()
-                .context("Failed to run brew uninstall")?;
+    }
 
-            if status.success() {
-                println!("{} {} uninstalled successfully!", "✓".green(), tool.name);
-            } else {
-                anyhow::bail!("brew uninstall failed for {}", tool.name);
+    if fixable > 0 {
+        println!(
+            "\n{} Run `ai-cli-apps doctor --fix` to apply {} safe {}",
+            "→".cyan(),
+            fixable,
+            if fixable == 1 { "fix" } else { "fixes" }
+        );
+    }
+    if failures > 0 {
+        anyhow::bail!("{} of {} checks failed", failures, checks.len());
+    }
+    Ok(())
+}
+
+/// Writes the exact version of every installed tool to `ai-cli.lock`.
+pub async fn handle_lock_command() -> Result<()> {
+    let tools = tools::catalog()?;
+    let mut lockfile = Lockfile::default();
+
+    for tool in &tools {
+        let Some(installed) = tool.installed_version().installed else {
+            continue;
+        };
+
+        let method = tool.installed_method();
+        let script_sha256 = match &method {
+            InstallMethod::Bootstrap(url) => {
+                let script =
+                    download_script(url, &format!("{} bootstrap script", tool.name)).await?;
+                Some(lockfile::sha256_hex(script.as_bytes()))
             }
+            _ => None,
+        };
+
+        if !tool.can_pin(&method) {
+            println!(
+                "{} {} cannot be pinned; `sync --locked` will only verify its version",
+                "!".yellow(),
+                tool.name
+            );
         }
+        println!(
+            "{} {} {}",
+            "✓".green(),
+            tool.name,
+            installed.version.bright_blue()
+        );
+
+        lockfile.tools.push(LockedTool {
+            identifier: tool.identifier.clone(),
+            version: installed.version,
+            method: method.kind().to_string(),
+            source: method.source().to_string(),
+            script_sha256,
+        });
     }
 
+    if lockfile.tools.is_empty() {
+        println!("{}", "No tools are currently installed.".yellow());
+        return Ok(());
+    }
+
+    let path = lockfile::target()?;
+    lockfile.save(&path)?;
+    println!(
+        "\n{} Wrote {} tools to {}",
+        "✓".green(),
+        lockfile.tools.len(),
+        path.display()
+    );
     Ok(())
 }
 
-async fn upgrade_tool(tool: &Tool) -> Result<()> {
-    println!("Upgrading {}...", tool.name.bright_cyan());
+/// Brings installed tools in line with `ai-cli.lock` (`--locked`) or the project manifest.
+pub async fn handle_sync_command(locked: bool) -> Result<()> {
+    if locked {
+        sync_lockfile().await
+    } else {
+        sync_project().await
+    }
+}
 
-    match &tool.install_method {
-        InstallMethod::Amp(_) => {
-            println!("{} Running `amp update`...", "→".cyan());
-            let status = Command::new("amp")
-                .arg("update")
-                .status()
-                .context("Failed to run `amp update`")?;
-
-            if status.success() {
-                println!("{} {} upgraded successfully!", "✓".green(), tool.name);
-                Ok(())
-            } else {
-                anyhow::bail!("`amp update` failed - see output above for details");
-            }
-        }
-        InstallMethod::Brew(formula) => {
-            println!("{} Running `brew upgrade {}`...", "→".cyan(), formula);
-            let status = Command::new("brew")
-                .args(["upgrade", formula])
-                .status()
-                .context("Failed to run brew upgrade")?;
-
-            if status.success() {
-                println!("{} {} upgraded successfully!", "✓".green(), tool.name);
-                Ok(())
-            } else {
-                anyhow::bail!("brew upgrade failed for {}", tool.name);
+/// Checks installed tools against the project manifest, failing when any requirement is unmet.
+pub fn handle_verify_command() -> Result<()> {
+    let tools = tools::catalog()?;
+    let (path, manifest) = ProjectManifest::discover()?;
+    println!("{} Verifying {}\n", "→".cyan(), path.display());
+
+    let mut violations = 0;
+    for (name, req) in &manifest.tools {
+        match check_requirement(&tools, name, req) {
+            Ok(version) => println!("{} {} {} satisfies {}", "✓".green(), name, version, req),
+            Err(violation) => {
+                println!("{} {}: {}", "✗".red(), name, violation);
+                violations += 1;
             }
         }
-        InstallMethod::Npm(package) => {
-            println!("{} Running `npm install -g {}`...", "→".cyan(), package);
-            let status = Command::new("npm")
-                .args(["install", "-g"])
-                .arg(package)
-                .status()
-                .context("Failed to run npm install")?;
-
-            if status.success() {
-                println!("{} {} upgraded successfully!", "✓".green(), tool.name);
-                Ok(())
-            } else {
-                anyhow::bail!("npm install failed for {}", tool.name);
+    }
+
+    if violations > 0 {
+        anyhow::bail!(
+            "{} of {} requirements in {} are not met",
+            violations,
+            manifest.tools.len(),
+            path.display()
+        );
+    }
+    println!("\n{}", "All requirements are met ✓".green());
+    Ok(())
+}
+
+/// The installed version when it satisfies `req`, otherwise why it does not.
+fn check_requirement(tools: &[Tool], name: &str, req: &VersionReq) -> Result<String, String> {
+    let tool = find_tool(tools, name).ok_or_else(|| "not a known tool".to_string())?;
+    let installed = tool
+        .installed_version()
+        .installed
+        .ok_or_else(|| format!("not installed (requires {})", req))?;
+    match &installed.parsed {
+        Some(version) if req.matches(version) => Ok(installed.version),
+        Some(_) => Err(format!("{} does not satisfy {}", installed.version, req)),
+        None => Err(format!(
+            "installed version '{}' could not be parsed (requires {})",
+            installed.version, req
+        )),
+    }
+}
+
+/// Installs or upgrades only the tools whose installed version misses its constraint.
+async fn sync_project() -> Result<()> {
+    let tools = tools::catalog()?;
+    let (path, manifest) = ProjectManifest::discover()?;
+    println!("{} Syncing {}\n", "→".cyan(), path.display());
+
+    let mut pending = Vec::new();
+    let mut already_fine = Vec::new();
+    let mut failures = Vec::new();
+    for (name, req) in &manifest.tools {
+        match (
+            find_tool(&tools, name),
+            check_requirement(&tools, name, req),
+        ) {
+            (None, _) => failures.push(format!("{}: not a known tool", name)),
+            (Some(_), Ok(installed)) => already_fine.push(format!("{} {}", name, installed)),
+            (Some(tool), Err(_)) => pending.push((tool, req)),
+        }
+    }
+
+    let mut versions: Vec<_> = pending
+        .iter()
+        .map(|(tool, _)| tool.installed_version())
+        .collect();
+    if !versions.is_empty() {
+        check_latest_versions(&mut versions, true, CacheMode::Refresh).await;
+    }
+
+    let mut changed = Vec::new();
+    for ((tool, req), version) in pending.into_iter().zip(versions) {
+        match sync_requirement(tool, req, &version).await {
+            Ok(()) => match check_requirement(&tools, &tool.identifier, req) {
+                Ok(installed) => changed.push(format!("{} {}", tool.name, installed)),
+                Err(violation) => failures.push(format!("{}: {}", tool.name, violation)),
+            },
+            Err(e) => failures.push(format!("{}: {:#}", tool.name, e)),
+        }
+    }
+
+    println!();
+    if !changed.is_empty() {
+        println!("{}", "Changed:".bright_green().bold());
+        for tool in &changed {
+            println!("  {} {}", "✓".green(), tool);
+        }
+    }
+    if !already_fine.is_empty() {
+        println!("{}", "Already satisfied:".bright_black().bold());
+        for tool in &already_fine {
+            println!("  {} {}", "✓".green(), tool.bright_black());
+        }
+    }
+    if !failures.is_empty() {
+        println!("{}", "Failed:".red().bold());
+        for failure in &failures {
+            println!("  {} {}", "✗".red(), failure);
+        }
+        anyhow::bail!("{} requirements could not be satisfied", failures.len());
+    }
+    Ok(())
+}
+
+/// Installs or upgrades to the latest release when it satisfies `req`, otherwise pins the
+/// highest matching npm release.
+async fn sync_requirement(tool: &Tool, req: &VersionReq, version: &ToolVersion) -> Result<()> {
+    let latest_matches = version
+        .latest
+        .as_deref()
+        .and_then(Version::parse)
+        .is_some_and(|latest| req.matches(&latest));
+
+    if latest_matches {
+        return if version.installed.is_some() {
+            upgrade_tool(tool, false, false).await
+        } else {
+            install_tool(tool, false, false).await
+        };
+    }
+
+    let method = tool.installed_method();
+    if let InstallMethod::Npm(package) = &method
+        && let Some(matching) = get_npm_matching(package, req).await
+    {
+        return install_version(tool, &method, &matching, None, false, false).await;
+    }
+
+    anyhow::bail!(
+        "no installable release satisfies {} (latest is {}); install one with `ai-cli-apps install {}@<version>`",
+        req,
+        version.latest.as_deref().unwrap_or("unknown"),
+        tool.identifier
+    )
+}
+
+/// Installs, upgrades or downgrades every tool in `ai-cli.lock` to its locked version.
+async fn sync_lockfile() -> Result<()> {
+    let tools = tools::catalog()?;
+    let lockfile = Lockfile::load(&lockfile::discover()?)?;
+
+    let mut failures = 0;
+    for locked in &lockfile.tools {
+        if let Err(e) = sync_locked_tool(&tools, locked).await {
+            println!("{} {}: {:#}", "✗".red(), locked.identifier, e);
+            failures += 1;
+        }
+    }
+
+    if failures > 0 {
+        anyhow::bail!(
+            "{} of {} tools failed to sync",
+            failures,
+            lockfile.tools.len()
+        );
+    }
+    println!("\n{}", "All tools match the lockfile ✓".green());
+    Ok(())
+}
+
+async fn sync_locked_tool(tools: &[Tool], locked: &LockedTool) -> Result<()> {
+    let tool = find_tool(tools, &locked.identifier)
+        .with_context(|| format!("'{}' is not a known tool", locked.identifier))?;
+
+    let installed = tool.installed_method();
+    let method = tool
+        .install_methods
+        .iter()
+        .chain([&installed])
+        .find(|m| m.kind() == locked.method && m.source() == locked.source)
+        .with_context(|| {
+            format!(
+                "locked as {} '{}', which {} no longer offers",
+                locked.method, locked.source, tool.name
+            )
+        })?;
+    if !method.is_available() {
+        anyhow::bail!(
+            "locked as {} '{}', but {} is not available here",
+            locked.method,
+            locked.source,
+            locked.method
+        );
+    }
+
+    if is_installed_at(tool, &locked.version) {
+        println!(
+            "{} {} {} matches the lockfile",
+            "✓".green(),
+            tool.name,
+            locked.version
+        );
+        return Ok(());
+    }
+    if !tool.can_pin(method) {
+        let installed = tool.installed_version().installed.map(|i| i.version);
+        return unpinnable_mismatch(tool, installed.as_deref(), &locked.version);
+    }
+
+    install_version(
+        tool,
+        method,
+        &locked.version,
+        locked.script_sha256.as_deref(),
+        false,
+        false,
+    )
+    .await
+}
+
+/// `sync --locked` can only verify a tool that cannot be pinned, so a version mismatch fails.
+fn unpinnable_mismatch(tool: &Tool, installed: Option<&str>, locked: &str) -> Result<()> {
+    anyhow::bail!(
+        "{} is {}, lockfile wants {}; this tool cannot be pinned, reinstall manually",
+        tool.name,
+        installed.unwrap_or("not installed"),
+        locked
+    )
+}
+
+async fn install_tool(tool: &Tool, dry_run: bool, review: bool) -> Result<()> {
+    let npm_tag = npm_tag(tool, tool.install_method()).await;
+    let mut plan = plan_install(tool, npm_tag);
+    if review {
+        plan.review_scripts();
+    }
+    if dry_run {
+        plan.print(&format!("install {}", tool.name));
+        return Ok(());
+    }
+
+    println!("Installing {}...", tool.name.bright_cyan());
+    execute_logged(tool, Action::Install, tool.install_method(), plan, false).await?;
+    println!("{} {} installed successfully!", "✓".green(), tool.name);
+    Ok(())
+}
+
+fn plan_install(tool: &Tool, npm_tag: &str) -> Plan {
+    let mut plan = Plan::default();
+    match tool.install_method() {
+        InstallMethod::Bootstrap(url) => {
+            plan.push(script_step(
+                url,
+                "bootstrap script",
+                bootstrap_args(tool),
+                None,
+            ));
+            plan.steps.extend(local_bin_step());
+        }
+        InstallMethod::Amp(url) => {
+            plan.push(script_step(url, "Amp installer", Vec::new(), None));
+            plan.steps.extend(local_bin_step());
+        }
+        InstallMethod::Brew(formula) => {
+            let mut command = vec!["brew", "install"];
+            if tool.channel == Channel::Next {
+                command.push("--HEAD");
+            }
+            command.push(formula);
+            plan.push(Step::run(&command));
+        }
+        InstallMethod::BrewCask(cask) => plan.push(Step::run(&["brew", "install", "--cask", cask])),
+        InstallMethod::Npm(package) => {
+            plan.push(Step::run(&[
+                "npm",
+                "install",
+                "-g",
+                &npm_spec(package, npm_tag),
+            ]));
+        }
+    }
+    plan
+}
+
+async fn uninstall_tool(
+    tool: &Tool,
+    remove_config: bool,
+    force: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let method = tool.installed_method();
+    let mut plan = plan_uninstall(tool, &method, remove_config)?;
+    plan.notes.splice(0..0, origin_note(tool, &method));
+    if dry_run {
+        plan.print(&format!("uninstall {}", tool.name));
+        return Ok(());
+    }
+
+    println!("Uninstalling {}...", tool.name.bright_cyan());
+    remove(tool, &method, plan, force).await
+}
+
+/// Removes the copy of `tool` managed by `method`.
+async fn remove_with(
+    tool: &Tool,
+    method: &InstallMethod,
+    remove_config: bool,
+    force: bool,
+) -> Result<()> {
+    let plan = plan_uninstall(tool, method, remove_config)?;
+    remove(tool, method, plan, force).await
+}
+
+async fn remove(tool: &Tool, method: &InstallMethod, plan: Plan, force: bool) -> Result<()> {
+    let runs_commands = plan
+        .steps
+        .iter()
+        .any(|step| matches!(step, Step::Run { .. }));
+    let removed_items = execute_logged(tool, Action::Uninstall, method, plan, force).await?;
+
+    if removed_items.is_empty() && !runs_commands {
+        println!("{} {} not found on system", "!".yellow(), tool.name);
+        return Ok(());
+    }
+    println!("{} {} uninstalled successfully!", "✓".green(), tool.name);
+    if !removed_items.is_empty() {
+        println!("{} Removed:", "→".cyan());
+        for item in removed_items {
+            println!("  - {}", item);
+        }
+    }
+    Ok(())
+}
+
+fn plan_uninstall(tool: &Tool, method: &InstallMethod, remove_config: bool) -> Result<Plan> {
+    Ok(plan_uninstall_in(
+        &paths::home_dir()?,
+        tool,
+        method,
+        remove_config,
+    ))
+}
+
+fn plan_uninstall_in(
+    home: &Path,
+    tool: &Tool,
+    method: &InstallMethod,
+    remove_config: bool,
+) -> Plan {
+    let mut plan = Plan::default();
+    match method {
+        InstallMethod::Bootstrap(_) => {
+            let mut binary_paths = vec![home.join(".local").join("bin").join(tool.binary_name())];
+            binary_paths.extend(tool.extra_binary_paths.iter().map(|extra| home.join(extra)));
+            for path in binary_paths {
+                if path.exists() {
+                    plan.push(Step::Archive {
+                        label: "binary",
+                        path,
+                    });
+                }
+            }
+
+            let versions = versions_dir(home, tool);
+            if versions.exists()
+                && let Some(share) = versions.parent()
+            {
+                plan.push(Step::Archive {
+                    label: "versions",
+                    path: share.to_path_buf(),
+                });
+            }
+
+            plan_config_removal(
+                &mut plan,
+                config_dirs(home, tool),
+                remove_config,
+                "use --remove-config to remove it",
+            );
+            plan.push(Step::CleanUpShellRc {
+                installer_marker: None,
+            });
+        }
+        InstallMethod::Amp(_) => {
+            let local_bin = home.join(".local").join("bin");
+            for shim in ["amp", "amp.bat"] {
+                let path = local_bin.join(shim);
+                if path.exists() {
+                    plan.push(Step::Archive {
+                        label: "shim",
+                        path,
+                    });
+                }
+            }
+
+            let amp_home = home.join(".amp");
+            if amp_home.exists() {
+                plan.push(Step::Archive {
+                    label: "AMP_HOME",
+                    path: amp_home,
+                });
+            }
+
+            let xdg = |variable: &str, fallback: PathBuf| {
+                std::env::var(variable)
+                    .map(PathBuf::from)
+                    .unwrap_or(fallback)
+                    .join("amp")
+            };
+            plan_config_removal(
+                &mut plan,
+                vec![
+                    xdg("XDG_CONFIG_HOME", home.join(".config")),
+                    xdg("XDG_DATA_HOME", home.join(".local").join("share")),
+                    xdg("XDG_CACHE_HOME", home.join(".cache")),
+                ],
+                remove_config,
+                "use --remove-config to delete them",
+            );
+            plan.push(Step::CleanUpShellRc {
+                installer_marker: Some("amp"),
+            });
+        }
+        InstallMethod::Npm(package) => {
+            plan_package_uninstall(
+                &mut plan,
+                &["npm", "uninstall", "-g", package],
+                config_dirs(home, tool),
+                remove_config,
+            );
+        }
+        InstallMethod::Brew(formula) => {
+            plan_package_uninstall(
+                &mut plan,
+                &["brew", "uninstall", formula],
+                config_dirs(home, tool),
+                remove_config,
+            );
+        }
+        InstallMethod::BrewCask(cask) => {
+            plan_package_uninstall(
+                &mut plan,
+                &["brew", "uninstall", "--cask", cask],
+                config_dirs(home, tool),
+                remove_config,
+            );
+        }
+    }
+    plan
+}
+
+/// A package manager deletes the package itself, so only its config can reach the archive.
+fn plan_package_uninstall(
+    plan: &mut Plan,
+    command: &[&str],
+    config_dirs: Vec<PathBuf>,
+    remove_config: bool,
+) {
+    plan.push(Step::run(command));
+    plan.notes.push(format!(
+        "{} deletes the package; only config can be restored from the archive",
+        command[0]
+    ));
+    plan_config_removal(
+        plan,
+        config_dirs,
+        remove_config,
+        "use --remove-config to remove it",
+    );
+}
+
+/// The tool's config directories, `~/.<identifier>` when the catalog names none.
+fn config_dirs(home: &Path, tool: &Tool) -> Vec<PathBuf> {
+    if tool.config_dirs.is_empty() {
+        vec![home.join(format!(".{}", tool.identifier))]
+    } else {
+        tool.config_dirs.iter().map(|dir| home.join(dir)).collect()
+    }
+}
+
+/// Archives the config directories that exist when asked to, and otherwise notes they are kept.
+fn plan_config_removal(plan: &mut Plan, dirs: Vec<PathBuf>, remove_config: bool, hint: &str) {
+    let existing: Vec<PathBuf> = dirs.into_iter().filter(|path| path.exists()).collect();
+    if remove_config {
+        plan.steps
+            .extend(existing.into_iter().map(Step::ArchiveConfig));
+        return;
+    }
+    for path in existing {
+        plan.notes
+            .push(format!("Keeping config {} ({})", path.display(), hint));
+    }
+}
+
+/// Installs an exact version, replacing whatever version is installed. For bootstrap tools an
+/// expected script digest refuses to run an installer that changed since it was locked.
+async fn install_version(
+    tool: &Tool,
+    method: &InstallMethod,
+    version: &str,
+    script_sha256: Option<&str>,
+    dry_run: bool,
+    review: bool,
+) -> Result<()> {
+    let mut plan = plan_install_version(tool, method, version, script_sha256)?;
+    if review {
+        plan.review_scripts();
+    }
+    if dry_run {
+        plan.print(&format!("install {} {}", tool.name, version));
+        return Ok(());
+    }
+
+    let action = if tool.is_installed() {
+        Action::Upgrade
+    } else {
+        Action::Install
+    };
+
+    println!(
+        "Installing {} {}...",
+        tool.name.bright_cyan(),
+        version.bright_blue()
+    );
+    execute_logged(tool, action, method, plan, false).await?;
+    println!(
+        "{} {} {} installed successfully!",
+        "✓".green(),
+        tool.name,
+        version
+    );
+    Ok(())
+}
+
+fn plan_install_version(
+    tool: &Tool,
+    method: &InstallMethod,
+    version: &str,
+    script_sha256: Option<&str>,
+) -> Result<Plan> {
+    if !tool.can_pin(method) {
+        anyhow::bail!(
+            "{} cannot be installed at a specific version: its installer always installs the newest release",
+            tool.name
+        );
+    }
+
+    let mut plan = Plan::default();
+    match method {
+        InstallMethod::Bootstrap(url) => {
+            plan.push(script_step(
+                url,
+                "bootstrap script",
+                vec![version],
+                script_sha256,
+            ));
+        }
+        InstallMethod::Npm(package) => {
+            plan.push(Step::run(&[
+                "npm",
+                "install",
+                "-g",
+                &format!("{}@{}", package, version),
+            ]));
+        }
+        InstallMethod::Brew(formula) => {
+            let versioned = format!("{}@{}", formula, version);
+            let known = Command::new("brew")
+                .args(["info", &versioned])
+                .output()
+                .context("Failed to run brew info")?
+                .status
+                .success();
+            if !known {
+                anyhow::bail!(
+                    "Homebrew has no versioned formula '{}'. Versioned formulae exist only for selected releases.",
+                    versioned
+                );
+            }
+
+            plan.push(Step::run(&["brew", "install", &versioned]));
+            // Versioned formulae are keg-only; link it so it shadows the unversioned formula.
+            plan.push(Step::Run {
+                command: vec!["brew".into(), "unlink".into(), formula.clone()],
+                may_fail: true,
+            });
+            plan.push(Step::run(&[
+                "brew",
+                "link",
+                "--overwrite",
+                "--force",
+                &versioned,
+            ]));
+        }
+        InstallMethod::Amp(_) | InstallMethod::BrewCask(_) => {
+            unreachable!("checked by can_pin")
+        }
+    }
+    Ok(plan)
+}
+
+fn is_installed_at(tool: &Tool, version: &str) -> bool {
+    let installed = tool.installed_version().installed;
+    match (installed.and_then(|i| i.parsed), Version::parse(version)) {
+        (Some(installed), Some(requested)) => installed == requested,
+        _ => false,
+    }
+}
+
+async fn upgrade_tool(tool: &Tool, dry_run: bool, review: bool) -> Result<()> {
+    let method = tool.installed_method();
+    let npm_tag = npm_tag(tool, &method).await;
+    let version_before = tool.installed_version().installed.map(|i| i.version);
+    let can_roll_back = version_before.as_deref().is_some_and(|version| {
+        paths::home_dir().is_ok_and(|home| can_roll_back(&home, tool, &method, version))
+    });
+    let mut plan = plan_upgrade(tool, &method, npm_tag, can_roll_back);
+    if review {
+        plan.review_scripts();
+    }
+    if dry_run {
+        plan.print(&format!("upgrade {}", tool.name));
+        return Ok(());
+    }
+
+    println!("Upgrading {}...", tool.name.bright_cyan());
+    let Err(e) = execute_logged(tool, Action::Upgrade, &method, plan, false).await else {
+        println!("{} {} upgraded successfully!", "✓".green(), tool.name);
+        return Ok(());
+    };
+
+    // A failed upgrade that left the tool running needs no rollback.
+    let Some(previous) = version_before.filter(|_| !tool.is_installed()) else {
+        return Err(e);
+    };
+    if !can_roll_back {
+        return Err(e).with_context(|| format!("upgrade failed; cannot roll back {}", tool.name));
+    }
+    println!("{} {:#}; rolling back to {}", "✗".red(), e, previous);
+    rollback(tool, &method, &previous).await.with_context(|| {
+        format!(
+            "{} is broken after the upgrade and could not be rolled back to {}",
+            tool.name, previous
+        )
+    })?;
+    Err(e).with_context(|| {
+        format!(
+            "Upgrading {} failed; rolled back to {}",
+            tool.name, previous
+        )
+    })
+}
+
+/// Goes back to the version installed before the last upgrade.
+pub async fn handle_rollback_command(tool_name: &str) -> Result<()> {
+    let tools = tools::catalog()?;
+    let tool = find_tool(&tools, tool_name).with_context(|| {
+        format!(
+            "Tool '{}' not found. Available tools: {}",
+            tool_name,
+            format_available_tools(&tools)
+        )
+    })?;
+
+    let Some(installed) = tool.installed_version().installed else {
+        println!("{} {} is not installed!", "!".yellow(), tool.name);
+        return Ok(());
+    };
+    let method = tool.installed_method();
+    let history = history::load(Some(&tool.identifier))?;
+    let previous = previous_version(
+        &paths::home_dir()?,
+        tool,
+        &method,
+        &history,
+        &installed.version,
+    )?;
+
+    rollback(tool, &method, &previous).await
+}
+
+/// The version to roll back to: the one before the last logged upgrade or rollback, else for
+/// bootstrap installs the newest older version the installer kept.
+fn previous_version(
+    home: &Path,
+    tool: &Tool,
+    method: &InstallMethod,
+    history: &[history::Entry],
+    installed: &str,
+) -> Result<String> {
+    match history::version_before_last_upgrade(history) {
+        Some(version) => Ok(version.to_string()),
+        None if matches!(method, InstallMethod::Bootstrap(_)) => {
+            newest_kept_version_before(home, tool, installed).with_context(|| {
+                format!(
+                    "No earlier version of {} to roll back to: no upgrade is logged and its installer kept none",
+                    tool.name
+                )
+            })
+        }
+        None => anyhow::bail!(
+            "No earlier version of {} to roll back to: no upgrade is logged in `ai-cli-apps history {}`",
+            tool.name,
+            tool.identifier
+        ),
+    }
+}
+
+async fn rollback(tool: &Tool, method: &InstallMethod, version: &str) -> Result<()> {
+    if is_installed_at(tool, version) {
+        println!(
+            "{} {} {} is already installed!",
+            "✓".green(),
+            tool.name,
+            version
+        );
+        return Ok(());
+    }
+
+    let plan = plan_rollback(tool, method, version)?;
+    println!(
+        "Rolling back {} to {}...",
+        tool.name.bright_cyan(),
+        version.bright_blue()
+    );
+    execute_logged(tool, Action::Rollback, method, plan, false).await?;
+    println!("{} {} rolled back to {}", "✓".green(), tool.name, version);
+    Ok(())
+}
+
+/// Relinks a version the bootstrap installer kept when there is one, and otherwise installs
+/// the version again.
+fn plan_rollback(tool: &Tool, method: &InstallMethod, version: &str) -> Result<Plan> {
+    plan_rollback_in(&paths::home_dir()?, tool, method, version)
+}
+
+fn plan_rollback_in(
+    home: &Path,
+    tool: &Tool,
+    method: &InstallMethod,
+    version: &str,
+) -> Result<Plan> {
+    let kept = versions_dir(home, tool).join(version);
+    if matches!(method, InstallMethod::Bootstrap(_)) && kept.is_file() {
+        let mut plan = Plan::default();
+        plan.push(Step::Link {
+            link: home.join(".local").join("bin").join(tool.binary_name()),
+            target: kept,
+        });
+        return Ok(plan);
+    }
+    plan_install_version(tool, method, version, None)
+}
+
+/// Whether `version` can be brought back after an upgrade: it can be installed again, or the
+/// bootstrap installer kept it.
+fn can_roll_back(home: &Path, tool: &Tool, method: &InstallMethod, version: &str) -> bool {
+    tool.can_pin(method)
+        || matches!(method, InstallMethod::Bootstrap(_))
+            && versions_dir(home, tool).join(version).is_file()
+}
+
+/// Where bootstrap installers such as Claude Code's keep one binary per version.
+fn versions_dir(home: &Path, tool: &Tool) -> PathBuf {
+    home.join(".local")
+        .join("share")
+        .join(tool.binary_name())
+        .join("versions")
+}
+
+/// The newest version in the installer's versions directory that is older than `installed`.
+fn newest_kept_version_before(home: &Path, tool: &Tool, installed: &str) -> Option<String> {
+    let installed = Version::parse(installed)?;
+    fs::read_dir(versions_dir(home, tool))
+        .ok()?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter_map(|name| Some((Version::parse(&name)?, name)))
+        .filter(|(version, _)| *version < installed)
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, name)| name)
+}
+
+fn plan_upgrade(tool: &Tool, method: &InstallMethod, npm_tag: &str, can_roll_back: bool) -> Plan {
+    let mut plan = Plan::default();
+    if let Some(upgrade_command) = tool.upgrade_command_for(method) {
+        let command: Vec<&str> = upgrade_command.iter().map(String::as_str).collect();
+        plan.push(Step::run(&command));
+    } else {
+        plan.notes.extend(origin_note(tool, method));
+        plan.push(upgrade_step(tool, method, npm_tag));
+    }
+    if !tool.check_command.is_empty() {
+        plan.push(Step::Verify {
+            check: tool.check_command.clone(),
+            rollback: can_roll_back,
+        });
+    }
+    plan
+}
+
+fn upgrade_step(tool: &Tool, method: &InstallMethod, npm_tag: &str) -> Step {
+    match method {
+        InstallMethod::Amp(url) => script_step(url, "Amp installer", Vec::new(), None),
+        InstallMethod::BrewCask(cask) => Step::run(&["brew", "upgrade", "--cask", cask]),
+        InstallMethod::Brew(formula) => {
+            let mut command = vec!["brew", "upgrade"];
+            if tool.channel == Channel::Next {
+                command.push("--fetch-HEAD");
             }
+            command.push(formula);
+            Step::run(&command)
+        }
+        InstallMethod::Npm(package) => {
+            Step::run(&["npm", "install", "-g", &npm_spec(package, npm_tag)])
         }
         InstallMethod::Bootstrap(url) => {
-            let is_cursor_agent = tool
-                .binary_name
-                .as_deref()
-                .map(|name| name == "cursor-agent")
-                .unwrap_or(false);
-
-            if is_cursor_agent {
-                println!("{} Running `cursor-agent upgrade`...", "→".cyan());
-                let status = Command::new("cursor-agent")
-                    .arg("upgrade")
-                    .status()
-                    .context("Failed to run cursor-agent upgrade")?;
-
-                if status.success() {
-                    println!("{} {} upgraded successfully!", "✓".green(), tool.name);
-                    Ok(())
-                } else {
-                    anyhow::bail!("cursor-agent upgrade failed");
+            script_step(url, "bootstrap script", bootstrap_args(tool), None)
+        }
+    }
+}
+
+fn script_step(url: &str, description: &str, args: Vec<&str>, sha256: Option<&str>) -> Step {
+    Step::RunScript {
+        url: url.to_string(),
+        description: description.to_string(),
+        args: args.into_iter().map(String::from).collect(),
+        sha256: sha256.map(String::from),
+        review: false,
+    }
+}
+
+/// Scripts link binaries into `~/.local/bin`, which new shells need on PATH.
+fn local_bin_step() -> Option<Step> {
+    let local_bin = shell::local_bin().ok()?;
+    (!shell::is_on_path(&local_bin)).then_some(Step::AddLocalBinToPath)
+}
+
+/// Runs `plan` and appends what happened to the history log, whether it succeeded or not.
+async fn execute_logged(
+    tool: &Tool,
+    action: Action,
+    method: &InstallMethod,
+    plan: Plan,
+    force: bool,
+) -> Result<Vec<String>> {
+    let installed_version = || tool.installed_version().installed.map(|i| i.version);
+    let version_before = installed_version();
+    let started = Instant::now();
+    let mut commands = Vec::new();
+    let result = execute(tool, plan, force, &mut commands).await;
+
+    let entry = history::Entry {
+        timestamp: cache::now(),
+        action,
+        tool: tool.identifier.clone(),
+        method: method.kind().to_string(),
+        version_before,
+        version_after: installed_version(),
+        commands,
+        success: result.is_ok(),
+        error: result.as_ref().err().map(|e| format!("{:#}", e)),
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+    if let Err(e) = history::append(&entry) {
+        println!("{} Could not write history: {:#}", "!".yellow(), e);
+    }
+    result
+}
+
+/// Runs `plan` step by step, stopping at the first failure, and records each command it runs
+/// in `commands`. Returns what was moved to the uninstall archive or edited out of shell rc
+/// files, for the summary.
+async fn execute(
+    tool: &Tool,
+    plan: Plan,
+    force: bool,
+    commands: &mut Vec<CommandRun>,
+) -> Result<Vec<String>> {
+    for note in &plan.notes {
+        println!("{} {}", "→".cyan(), note);
+    }
+
+    let configs: Vec<&PathBuf> = plan
+        .steps
+        .iter()
+        .filter_map(|step| match step {
+            Step::ArchiveConfig(path) => Some(path),
+            _ => None,
+        })
+        .collect();
+    let remove_configs = if configs.is_empty() || force {
+        true
+    } else {
+        println!("{} Config directories found:", "→".cyan());
+        for path in &configs {
+            println!("  - {}", path.display());
+        }
+        println!(
+            "{} Remove config directories? (contains settings and history; kept in the uninstall archive) [y/N]",
+            "?".yellow()
+        );
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        input.trim().eq_ignore_ascii_case("y")
+    };
+    if !remove_configs {
+        println!("{} Keeping config directories", "→".cyan());
+    }
+
+    let mut archive = Archive::create(&tool.identifier)?;
+    let mut removed_items = Vec::new();
+    for step in plan.steps {
+        match step {
+            Step::RunScript {
+                url,
+                description,
+                args,
+                sha256,
+                review,
+            } => {
+                let script = download_script(&url, &description).await?;
+                if let Some(expected) = sha256 {
+                    let actual = lockfile::sha256_hex(script.as_bytes());
+                    if actual != expected {
+                        anyhow::bail!(
+                            "The {} {} changed since it was locked (expected sha256 {}, got {}). Review it and run `ai-cli-apps lock` again.",
+                            tool.name,
+                            description,
+                            expected,
+                            actual
+                        );
+                    }
+                }
+                approve_script(&url, &description, &script, review)?;
+                let mut command_line = format!("bash {}", url);
+                for arg in &args {
+                    command_line.push(' ');
+                    command_line.push_str(arg);
+                }
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                let status = run_script(&script, &description, &args);
+                commands.push(CommandRun {
+                    command: command_line,
+                    exit_code: status.as_ref().ok().and_then(|status| status.code()),
+                });
+                if !status?.success() {
+                    anyhow::bail!("Installation failed - see output above for details");
                 }
-            } else {
-                run_install_script(url, "bootstrap_upgrade.sh", "bootstrap script").await?;
-                println!("{} {} upgraded successfully!", "✓".green(), tool.name);
-                Ok(())
+            }
+            Step::Run { command, may_fail } => {
+                let command_line = command.join(" ");
+                println!("{} Running `{}`...", "→".cyan(), command_line);
+                let status = Command::new(&command[0]).args(&command[1..]).status();
+                commands.push(CommandRun {
+                    command: command_line.clone(),
+                    exit_code: status.as_ref().ok().and_then(|status| status.code()),
+                });
+                let status = status.with_context(|| format!("Failed to run `{}`", command_line))?;
+                if !status.success() && !may_fail {
+                    anyhow::bail!("`{}` failed - see output above for details", command_line);
+                }
+            }
+            Step::Archive { label, path } => {
+                archive.stash(&path)?;
+                removed_items.push(format!("{}: {}", label, path.display()));
+            }
+            Step::ArchiveConfig(path) => {
+                if remove_configs {
+                    archive.stash(&path)?;
+                    removed_items.push(format!("config: {}", path.display()));
+                }
+            }
+            Step::Link { link, target } => relink(&link, &target)?,
+            Step::Verify { check, .. } => {
+                if !tool.is_installed() {
+                    anyhow::bail!("`{}` fails after the upgrade", check.join(" "));
+                }
+            }
+            Step::AddLocalBinToPath => put_local_bin_on_path(),
+            Step::CleanUpShellRc { installer_marker } => {
+                removed_items.extend(clean_up_shell_rc(installer_marker));
+            }
+        }
+    }
+
+    if !archive.is_empty() {
+        println!(
+            "{} Archived to {}; undo with `ai-cli-apps restore {}`",
+            "→".cyan(),
+            archive.dir().display(),
+            tool.identifier
+        );
+    }
+    Ok(removed_items)
+}
+
+/// Lets an installer script run only once its content is approved. An approved script runs
+/// as is, a changed one is shown as a diff against the approved one, and both a changed and a
+/// never-seen script need a yes before they run and are recorded. `strict_scripts` refuses them
+/// instead of asking; `review` pages through the script and asks first in every case.
+fn approve_script(url: &str, description: &str, script: &str, review: bool) -> Result<()> {
+    let strict = Settings::load()?.strict_scripts;
+    let mut store = TrustStore::load()?;
+    let sha256 = lockfile::sha256_hex(script.as_bytes());
+
+    match store.check(url, &sha256) {
+        Verdict::Approved if !review => return Ok(()),
+        Verdict::Approved => page(script),
+        Verdict::Changed(approved) => {
+            println!(
+                "{} The {} changed since you approved it {} ago:",
+                "!".yellow(),
+                description,
+                cache::format_age(approved.age())
+            );
+            for line in trust::diff(&approved.content, script).lines() {
+                match line.chars().next() {
+                    Some('+') => println!("{}", line.green()),
+                    Some('-') => println!("{}", line.red()),
+                    Some('@') => println!("{}", line.cyan()),
+                    _ => println!("{}", line),
+                }
+            }
+            if strict && !review {
+                anyhow::bail!(
+                    "Strict mode refuses the changed {} (sha256 {}). Run with --review to approve it.",
+                    description,
+                    sha256
+                );
+            }
+            if review {
+                page(script);
             }
         }
+        Verdict::Unknown if review => page(script),
+        Verdict::Unknown if strict => anyhow::bail!(
+            "Strict mode refuses the {} from {}: sha256 {} is not approved. Run with --review to read and approve it.",
+            description,
+            url,
+            sha256
+        ),
+        Verdict::Unknown => println!(
+            "{} The {} has not been approved before; use --review to read it first",
+            "!".yellow(),
+            description
+        ),
+    }
+
+    println!(
+        "{} Run the {} from {} (sha256 {})? [y/N]",
+        "?".yellow(),
+        description,
+        url,
+        sha256
+    );
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    if !input.trim().eq_ignore_ascii_case("y") {
+        anyhow::bail!("The {} was not approved", description);
+    }
+    store.approve(url, script);
+    store.save()
+}
+
+/// Shows `text` in `$PAGER` (`less` by default), or prints it when there is no terminal or
+/// the pager cannot be started.
+fn page(text: &str) {
+    if io::stdout().is_terminal() {
+        let pager = std::env::var("PAGER").unwrap_or_else(|_| "less".to_string());
+        let mut words = pager.split_whitespace();
+        if let Some(program) = words.next()
+            && let Ok(mut child) = Command::new(program)
+                .args(words)
+                .stdin(Stdio::piped())
+                .spawn()
+        {
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            let _ = child.wait();
+            return;
+        }
+    }
+    println!("{}", text);
+}
+
+/// Points the symlink at `link` to `target`, swapping it in with a rename so the tool is never
+/// missing.
+fn relink(link: &Path, target: &Path) -> Result<()> {
+    let mut temp = link.as_os_str().to_owned();
+    temp.push(".ai-cli-apps-new");
+    let temp = PathBuf::from(temp);
+    let _ = fs::remove_file(&temp);
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(target, &temp)
+        .with_context(|| format!("Failed to link {}", link.display()))?;
+    #[cfg(not(unix))]
+    fs::copy(target, &temp).with_context(|| format!("Failed to copy {}", target.display()))?;
+    fs::rename(&temp, link).with_context(|| format!("Failed to replace {}", link.display()))
+}
+
+/// Adds `~/.local/bin` to the shell rc files after a script install when it is not on PATH.
+/// A failure is reported but does not fail the install.
+fn put_local_bin_on_path() {
+    let result = shell::local_bin().and_then(|local_bin| {
+        if shell::is_on_path(&local_bin) {
+            Ok(Vec::new())
+        } else {
+            shell::add_local_bin()
+        }
+    });
+    match result {
+        Ok(edits) if edits.is_empty() => {}
+        Ok(edits) => {
+            for edit in &edits {
+                println!("{} Added ~/.local/bin to PATH in {}", "→".cyan(), edit);
+            }
+            println!("{} Open a new shell to use it", "→".cyan());
+        }
+        Err(e) => println!("{} Could not update shell rc files: {:#}", "!".yellow(), e),
+    }
+}
+
+/// Drops PATH entries that no longer lead anywhere: the `# marker` lines an installer added,
+/// and the managed `~/.local/bin` block once that directory is empty.
+fn clean_up_shell_rc(installer_marker: Option<&str>) -> Vec<String> {
+    let mut edits = Vec::new();
+    if let Some(marker) = installer_marker {
+        match shell::remove_installer_lines(marker) {
+            Ok(removed) => edits.extend(removed),
+            Err(e) => println!("{} Could not update shell rc files: {:#}", "!".yellow(), e),
+        }
+    }
+
+    let local_bin_empty = shell::local_bin()
+        .map(|dir| fs::read_dir(dir).map_or(true, |mut entries| entries.next().is_none()))
+        .unwrap_or(false);
+    if local_bin_empty {
+        match shell::remove_local_bin() {
+            Ok(removed) => edits.extend(removed),
+            Err(e) => println!("{} Could not update shell rc files: {:#}", "!".yellow(), e),
+        }
+    }
+
+    edits
+        .iter()
+        .map(|edit| format!("PATH entry: {}", edit))
+        .collect()
+}
+
+/// Points out when the installed copy came from somewhere other than the catalog's choice.
+fn origin_note(tool: &Tool, method: &InstallMethod) -> Option<String> {
+    let preferred = tool.install_method();
+    if method.kind() == preferred.kind() && method.source() == preferred.source() {
+        return None;
+    }
+    Some(format!(
+        "Installed with {} ({}), not {}; using {}",
+        method.kind(),
+        method.source(),
+        preferred.kind(),
+        method.kind()
+    ))
+}
+
+/// The dist-tag the tool's channel resolves to for an npm package, as the latest-version lookup
+/// resolves it. Without an answer from the registry the channel's own tag is used.
+async fn npm_tag(tool: &Tool, method: &InstallMethod) -> &'static str {
+    let preferred = tool.channel.npm_tags()[0];
+    match method {
+        InstallMethod::Npm(package) => get_npm_dist_tag(package, tool.channel)
+            .await
+            .unwrap_or(preferred),
+        _ => preferred,
+    }
+}
+
+/// `package@tag` for tags other than npm's default `latest`.
+fn npm_spec(package: &str, tag: &str) -> String {
+    match tag {
+        "latest" => package.to_string(),
+        tag => format!("{}@{}", package, tag),
     }
 }
 
-async fn run_install_script(url: &str, temp_filename: &str, description: &str) -> Result<()> {
+/// Bootstrap scripts that accept a target are told which channel to install.
+fn bootstrap_args(tool: &Tool) -> Vec<&'static str> {
+    if tool.accepts_target {
+        vec![tool.channel.as_str()]
+    } else {
+        Vec::new()
+    }
+}
+
+async fn download_script(url: &str, description: &str) -> Result<String> {
     println!("{} Downloading {}...", "→".cyan(), description);
 
-    let script = reqwest::get(url)
+    reqwest::get(url)
         .await
+        .and_then(|response| response.error_for_status())
         .with_context(|| format!("Failed to download {}", description))?
         .text()
         .await
-        .with_context(|| format!("Failed to read {}", description))?;
-
-    let temp_dir = std::env::temp_dir();
-    let script_path = temp_dir.join(temp_filename);
-    fs::write(&script_path, script).with_context(|| format!("Failed to write {}", description))?;
-
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(&script_path)?.permissions();
-        perms.set_mode(0o755);
-        fs::set_permissions(&script_path, perms)?;
-    }
+        .with_context(|| format!("Failed to read {}", description))
+}
 
+/// Passes the verified script to `bash -c` so the bytes that run are the ones that were checked;
+/// a file on disk could be swapped between the check and the exec.
+fn run_script(script: &str, description: &str, args: &[&str]) -> Result<ExitStatus> {
     println!("{} Running {}...", "→".cyan(), description);
     println!();
 
-    let status = Command::new("bash")
-        .arg(&script_path)
+    let status = script_command(script, args)
         .status()
         .context("Failed to run install script")?;
 
-    let _ = fs::remove_file(&script_path);
-
     println!();
-    if status.success() {
-        Ok(())
-    } else {
-        anyhow::bail!("Installation failed - see output above for details");
+    Ok(status)
+}
+
+/// Leaves stdin attached to the terminal so installer prompts read from the user, not the script.
+fn script_command(script: &str, args: &[&str]) -> Command {
+    let mut command = Command::new("bash");
+    command.arg("-c").arg(script).arg("bash").args(args);
+    command
+}
+
+/// Splits `claude@1.0.80` into the tool name and the requested version.
+fn parse_tool_spec(spec: &str) -> (&str, Option<&str>) {
+    match spec.split_once('@') {
+        Some((name, version)) if !version.trim().is_empty() => (name, Some(version.trim())),
+        Some((name, _)) => (name, None),
+        None => (spec, None),
     }
 }
 
+fn format_channels(channels: &[Channel]) -> String {
+    let names: Vec<&str> = channels.iter().map(|c| c.as_str()).collect();
+    format!("({})", names.join(", "))
+}
+
 fn format_available_tools(tools: &[Tool]) -> String {
     tools
         .iter()
-        .map(|t| {
-            if let Some(bin) = &t.binary_name {
-                format!("{} ({})", t.name, bin)
-            } else {
-                t.name.clone()
-            }
-        })
+        .map(|t| format!("{} ({})", t.name, t.identifier))
         .collect::<Vec<_>>()
         .join(", ")
 }
 
 fn find_tool<'a>(tools: &'a [Tool], name: &str) -> Option<&'a Tool> {
-    tools.iter().find(|t| {
-        t.name.eq_ignore_ascii_case(name)
-            || t.binary_name
-                .as_ref()
-                .map(|b| b.eq_ignore_ascii_case(name))
-                .unwrap_or(false)
-    })
+    tools
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(name) || t.identifier.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        can_roll_back, kept_copy, parse_tool_spec, plan_install, plan_rollback_in,
+        plan_uninstall_in, plan_upgrade, previous_version, removal_method, script_command,
+        split_held, unpinnable_mismatch,
+    };
+    use crate::{
+        history::{Action, Entry},
+        plan::Step,
+        tools::{Detection, InstallMethod, InstalledVersion, Tool, ToolVersion},
+        version::Version,
+    };
+    use std::{collections::BTreeSet, fs, io::Write, process::Stdio};
+
+    fn tool(source: &str) -> Tool {
+        toml::from_str(source).unwrap()
+    }
+
+    #[test]
+    fn it_plans_installs_for_each_method() {
+        let claude = tool(
+            r#"
+            name = "Claude Code"
+            identifier = "claude"
+            install = { bootstrap = "https://example.com/bootstrap.sh" }
+            check = ["claude", "--version"]
+            channel = "stable"
+            accepts_target = true
+            "#,
+        );
+        let plan = plan_install(&claude, "latest");
+        assert!(matches!(
+            &plan.steps[0],
+            Step::RunScript { url, args, sha256: None, review: false, .. }
+                if url == "https://example.com/bootstrap.sh" && args == &["stable"]
+        ));
+
+        let copilot = tool(
+            r#"
+            name = "Copilot CLI"
+            identifier = "copilot"
+            install = { npm = "@github/copilot" }
+            check = ["copilot", "--version"]
+            "#,
+        );
+        assert_eq!(
+            plan_install(&copilot, "latest").steps,
+            vec![Step::run(&["npm", "install", "-g", "@github/copilot"])]
+        );
+        assert_eq!(
+            plan_install(&copilot, "next").steps,
+            vec![Step::run(&["npm", "install", "-g", "@github/copilot@next"])]
+        );
+
+        let opencode = tool(
+            r#"
+            name = "OpenCode"
+            identifier = "opencode"
+            install = { brew = "opencode" }
+            check = ["opencode", "--version"]
+            channel = "next"
+            "#,
+        );
+        assert_eq!(
+            plan_install(&opencode, "latest").steps,
+            vec![Step::run(&["brew", "install", "--HEAD", "opencode"])]
+        );
+    }
+
+    #[test]
+    fn it_plans_upgrades_with_a_verify_step() {
+        let copilot = tool(
+            r#"
+            name = "Copilot CLI"
+            identifier = "copilot"
+            install = { npm = "@github/copilot" }
+            check = ["copilot", "--version"]
+            "#,
+        );
+        let method = copilot.install_method().clone();
+        assert_eq!(
+            plan_upgrade(&copilot, &method, "latest", true).steps,
+            vec![
+                Step::run(&["npm", "install", "-g", "@github/copilot"]),
+                Step::Verify {
+                    check: vec!["copilot".to_string(), "--version".to_string()],
+                    rollback: true,
+                },
+            ]
+        );
+
+        let cursor = tool(
+            r#"
+            name = "Cursor Agent"
+            identifier = "cursor-agent"
+            install = { bootstrap = "https://example.com/install" }
+            check = ["cursor-agent", "--version"]
+            upgrade = ["cursor-agent", "upgrade"]
+            "#,
+        );
+        let method = cursor.install_method().clone();
+        assert_eq!(
+            plan_upgrade(&cursor, &method, "latest", true).steps[0],
+            Step::run(&["cursor-agent", "upgrade"])
+        );
+    }
+
+    #[test]
+    fn it_upgrades_a_package_manager_copy_without_the_tool_upgrade_command() {
+        let amp = tool(
+            r#"
+            name = "Amp"
+            identifier = "amp"
+            install = { amp = "https://example.com/amp-install" }
+            check = ["amp", "--version"]
+            upgrade = ["amp", "update"]
+            "#,
+        );
+        let scripted = amp.install_method().clone();
+        assert_eq!(
+            plan_upgrade(&amp, &scripted, "latest", false).steps[0],
+            Step::run(&["amp", "update"])
+        );
+
+        let npm = InstallMethod::Npm("@sourcegraph/amp".to_string());
+        assert_eq!(
+            plan_upgrade(&amp, &npm, "latest", true).steps[0],
+            Step::run(&["npm", "install", "-g", "@sourcegraph/amp"])
+        );
+    }
+
+    #[test]
+    fn it_only_promises_a_rollback_when_the_version_can_come_back() {
+        let temp = tempfile::tempdir().unwrap();
+        let home = temp.path();
+        let amp = tool(
+            r#"
+            name = "Amp"
+            identifier = "amp"
+            install = { amp = "https://example.com/amp-install" }
+            check = ["amp", "--version"]
+            "#,
+        );
+        let method = amp.install_method().clone();
+        assert!(!can_roll_back(home, &amp, &method, "0.0.1"));
+        let plan = plan_upgrade(&amp, &method, "latest", false);
+        assert_eq!(
+            plan.steps.last().unwrap().to_string(),
+            "Run `amp --version` to check the upgrade"
+        );
+
+        let cursor = tool(
+            r#"
+            name = "Cursor Agent"
+            identifier = "cursor-agent"
+            install = { bootstrap = "https://example.com/install" }
+            check = ["cursor-agent", "--version"]
+            "#,
+        );
+        let method = cursor.install_method().clone();
+        assert!(!can_roll_back(home, &cursor, &method, "2025.10.01"));
+        let versions = home.join(".local/share/cursor-agent/versions");
+        fs::create_dir_all(&versions).unwrap();
+        fs::write(versions.join("2025.10.01"), "").unwrap();
+        assert!(can_roll_back(home, &cursor, &method, "2025.10.01"));
+
+        assert!(can_roll_back(
+            home,
+            &claude(),
+            &claude().install_method().clone(),
+            "2.0.1"
+        ));
+    }
+
+    #[test]
+    fn it_archives_bootstrap_installs_on_uninstall() {
+        let temp = tempfile::tempdir().unwrap();
+        let home = temp.path();
+        let binary = home.join(".local/bin/acme-agent");
+        let share = home.join(".local/share/acme-agent");
+        let config = home.join(".acme");
+        fs::create_dir_all(binary.parent().unwrap()).unwrap();
+        fs::write(&binary, "").unwrap();
+        fs::create_dir_all(share.join("versions")).unwrap();
+        fs::create_dir_all(&config).unwrap();
+
+        let acme = tool(
+            r#"
+            name = "Acme Agent"
+            identifier = "acme"
+            install = { bootstrap = "https://example.com/install.sh" }
+            check = ["acme-agent", "--version"]
+            "#,
+        );
+        let method = acme.install_method().clone();
+
+        let kept = plan_uninstall_in(home, &acme, &method, false);
+        assert_eq!(
+            kept.steps,
+            vec![
+                Step::Archive {
+                    label: "binary",
+                    path: binary.clone(),
+                },
+                Step::Archive {
+                    label: "versions",
+                    path: share.clone(),
+                },
+                Step::CleanUpShellRc {
+                    installer_marker: None,
+                },
+            ]
+        );
+        assert!(
+            kept.notes[0].starts_with("Keeping config"),
+            "{:?}",
+            kept.notes
+        );
+
+        let removed = plan_uninstall_in(home, &acme, &method, true);
+        assert!(removed.steps.contains(&Step::ArchiveConfig(config)));
+        assert!(binary.exists(), "planning must not touch the filesystem");
+    }
+
+    #[test]
+    fn it_leaves_package_removal_to_the_package_manager() {
+        let temp = tempfile::tempdir().unwrap();
+        let gemini = tool(
+            r#"
+            name = "Gemini CLI"
+            identifier = "gemini"
+            install = [{ brew = "gemini-cli" }, { npm = "@google/gemini-cli" }]
+            check = ["gemini", "--version"]
+            "#,
+        );
+
+        let npm = InstallMethod::Npm("@google/gemini-cli".to_string());
+        let plan = plan_uninstall_in(temp.path(), &gemini, &npm, true);
+        assert_eq!(
+            plan.steps,
+            vec![Step::run(&["npm", "uninstall", "-g", "@google/gemini-cli"])]
+        );
+        assert!(plan.notes[0].contains("only config can be restored"));
+
+        let brew = InstallMethod::Brew("gemini-cli".to_string());
+        assert_eq!(
+            plan_uninstall_in(temp.path(), &gemini, &brew, false).steps,
+            vec![Step::run(&["brew", "uninstall", "gemini-cli"])]
+        );
+    }
+
+    #[test]
+    fn it_leaves_stdin_to_the_user_when_running_a_script() {
+        let script = "read -r answer\necho \"answer=$answer arg=$1\"\necho done\n";
+        let mut child = script_command(script, &["stable"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(b"yes\n").unwrap();
+        let output = child.wait_with_output().unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "answer=yes arg=stable\ndone\n"
+        );
+    }
+
+    #[test]
+    fn it_fails_verification_of_a_locked_tool_that_cannot_be_pinned() {
+        let amp = tool(
+            r#"
+            name = "Amp"
+            identifier = "amp"
+            install = { amp = "https://example.com/amp-install" }
+            check = ["amp", "--version"]
+            "#,
+        );
+        let err = unpinnable_mismatch(&amp, Some("0.0.2"), "0.0.1").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Amp is 0.0.2, lockfile wants 0.0.1; this tool cannot be pinned, reinstall manually"
+        );
+    }
+
+    #[test]
+    fn it_splits_version_from_tool_spec() {
+        assert_eq!(parse_tool_spec("claude@1.0.80"), ("claude", Some("1.0.80")));
+        assert_eq!(parse_tool_spec("copilot"), ("copilot", None));
+        assert_eq!(parse_tool_spec("codex@"), ("codex", None));
+    }
+
+    fn claude() -> Tool {
+        tool(
+            r#"
+            name = "Claude Code"
+            identifier = "claude"
+            install = { bootstrap = "https://example.com/bootstrap.sh" }
+            check = ["claude", "--version"]
+            accepts_target = true
+            "#,
+        )
+    }
+
+    fn change(action: Action, before: &str, after: &str) -> Entry {
+        Entry {
+            timestamp: 1_000,
+            action,
+            tool: "claude".to_string(),
+            method: "bootstrap".to_string(),
+            version_before: Some(before.to_string()),
+            version_after: Some(after.to_string()),
+            commands: Vec::new(),
+            success: true,
+            error: None,
+            duration_ms: 1_000,
+        }
+    }
+
+    #[test]
+    fn it_picks_the_previous_version_from_history_then_kept_versions() {
+        let temp = tempfile::tempdir().unwrap();
+        let home = temp.path();
+        let claude = claude();
+        let method = claude.install_method().clone();
+        let versions = home.join(".local/share/claude/versions");
+        fs::create_dir_all(&versions).unwrap();
+        for kept in ["2.0.1", "2.0.3", "2.0.9"] {
+            fs::write(versions.join(kept), "").unwrap();
+        }
+
+        let upgraded = [change(Action::Upgrade, "2.0.1", "2.0.5")];
+        assert_eq!(
+            previous_version(home, &claude, &method, &upgraded, "2.0.5").unwrap(),
+            "2.0.1"
+        );
+        let rolled_back = [
+            change(Action::Upgrade, "2.0.1", "2.0.5"),
+            change(Action::Rollback, "2.0.5", "2.0.1"),
+        ];
+        assert_eq!(
+            previous_version(home, &claude, &method, &rolled_back, "2.0.1").unwrap(),
+            "2.0.5"
+        );
+        assert_eq!(
+            previous_version(home, &claude, &method, &[], "2.0.5").unwrap(),
+            "2.0.3"
+        );
+
+        let npm = InstallMethod::Npm("@anthropic-ai/claude-code".to_string());
+        let err = previous_version(home, &claude, &npm, &[], "2.0.5").unwrap_err();
+        assert!(err.to_string().contains("no upgrade is logged"), "{}", err);
+    }
+
+    #[test]
+    fn it_relinks_a_kept_version_and_reinstalls_a_pruned_one() {
+        let temp = tempfile::tempdir().unwrap();
+        let home = temp.path();
+        let claude = claude();
+        let method = claude.install_method().clone();
+        let versions = home.join(".local/share/claude/versions");
+        fs::create_dir_all(&versions).unwrap();
+        fs::write(versions.join("2.0.1"), "").unwrap();
+
+        let kept = plan_rollback_in(home, &claude, &method, "2.0.1").unwrap();
+        assert_eq!(
+            kept.steps,
+            vec![Step::Link {
+                link: home.join(".local/bin/claude"),
+                target: versions.join("2.0.1"),
+            }]
+        );
+
+        let pruned = plan_rollback_in(home, &claude, &method, "2.0.0").unwrap();
+        assert!(matches!(
+            pruned.steps.as_slice(),
+            [Step::RunScript { args, .. }] if args == &["2.0.0"]
+        ));
+    }
+
+    #[test]
+    fn it_keeps_the_copy_installed_with_the_requested_method() {
+        let gemini = tool(
+            r#"
+            name = "Gemini CLI"
+            identifier = "gemini"
+            install = [{ brew = "gemini-cli" }, { npm = "@google/gemini-cli" }]
+            check = ["gemini", "--version"]
+            "#,
+        );
+        let npm = InstallMethod::Npm("@google/gemini-cli".to_string());
+        let brew = InstallMethod::Brew("gemini-cli".to_string());
+        let methods = [Some(npm.clone()), None, Some(brew.clone())];
+
+        assert_eq!(kept_copy(&gemini, &methods, None).unwrap(), 0);
+        assert_eq!(kept_copy(&gemini, &methods, Some("brew")).unwrap(), 2);
+        let err = kept_copy(&gemini, &methods, Some("bootstrap")).unwrap_err();
+        assert!(
+            err.to_string().contains("Installed with: npm, brew"),
+            "{}",
+            err
+        );
+
+        assert!(std::ptr::eq(
+            removal_method(Some(&npm), Some(&brew)).unwrap(),
+            &npm
+        ));
+        assert!(removal_method(None, Some(&brew)).is_err());
+        let err = removal_method(Some(&brew), Some(&brew)).unwrap_err();
+        assert!(
+            err.to_string().contains("same brew as the kept copy"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn it_leaves_held_tools_out_of_bulk_upgrades() {
+        let tools: Vec<Tool> = ["claude", "codex", "gemini"]
+            .iter()
+            .map(|id| {
+                tool(&format!(
+                    "name = \"{id}\"\nidentifier = \"{id}\"\ninstall = {{ npm = \"{id}\" }}\ncheck = [\"{id}\"]"
+                ))
+            })
+            .collect();
+        let version = |id: &str, installed: &str, latest: &str| {
+            let mut version = ToolVersion::new(id)
+                .with_identifier(id)
+                .with_installed(Some(InstalledVersion {
+                    version: installed.to_string(),
+                    parsed: Version::parse(installed),
+                    components: Vec::new(),
+                    raw_output: installed.to_string(),
+                    binary_path: None,
+                    detection: Detection::Path,
+                    origin: None,
+                }));
+            version.latest = Some(latest.to_string());
+            version
+        };
+        let versions = [
+            version("claude", "2.0.1", "2.0.5"),
+            version("codex", "0.50.0", "0.50.0"),
+            version("gemini", "0.9.0", "0.10.0"),
+        ];
+        let held = BTreeSet::from(["gemini".to_string(), "codex".to_string()]);
+
+        let (upgradable, kept_back) = split_held(&tools, &versions, &held);
+        let upgradable: Vec<&str> = upgradable
+            .iter()
+            .map(|(tool, _)| tool.identifier.as_str())
+            .collect();
+        let kept_back: Vec<&str> = kept_back.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(upgradable, vec!["claude"]);
+        assert_eq!(kept_back, vec!["gemini"]);
+    }
 }
@@ -0,0 +1,328 @@
+use std::{
+    cmp::Reverse,
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{cache, paths};
+
+const ARCHIVE_DIR: &str = "archive";
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Binaries and config one uninstall moved aside, kept in
+/// `~/.local/share/ai-cli-apps/archive/<tool>/<unix time>/` until `restore` or retention.
+#[derive(Debug)]
+pub struct Archive {
+    dir: PathBuf,
+    manifest: Manifest,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Manifest {
+    tool: String,
+    created_at: u64,
+    entries: Vec<Entry>,
+}
+
+/// A file or directory moved into an archive, and where it came from.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Entry {
+    pub original: PathBuf,
+    stored: String,
+}
+
+/// Outcome of [`Archive::restore`].
+#[derive(Debug, Default)]
+pub struct Restored {
+    pub restored: Vec<PathBuf>,
+    /// Paths left in the archive because something exists at the original location again.
+    pub conflicts: Vec<PathBuf>,
+}
+
+impl Archive {
+    fn root() -> Result<PathBuf> {
+        Ok(paths::data_dir()?.join(ARCHIVE_DIR))
+    }
+
+    /// A new, empty archive for `tool`; nothing is written until the first [`Archive::stash`].
+    pub fn create(tool: &str) -> Result<Self> {
+        Ok(Self::create_in(&Self::root()?, tool, cache::now()))
+    }
+
+    fn create_in(root: &Path, tool: &str, now: u64) -> Self {
+        let tool_dir = root.join(tool);
+        let mut dir = tool_dir.join(now.to_string());
+        let mut suffix = 1;
+        while dir.exists() {
+            dir = tool_dir.join(format!("{}-{}", now, suffix));
+            suffix += 1;
+        }
+        Self {
+            dir,
+            manifest: Manifest {
+                tool: tool.to_string(),
+                created_at: now,
+                entries: Vec::new(),
+            },
+        }
+    }
+
+    /// Moves `path` into the archive, recording where it came from first so nothing is moved
+    /// that the manifest cannot bring back.
+    pub fn stash(&mut self, path: &Path) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let stored = format!("{}-{}", self.manifest.entries.len(), name);
+
+        self.manifest.entries.push(Entry {
+            original: path.to_path_buf(),
+            stored: stored.clone(),
+        });
+        if let Err(e) = self.save() {
+            self.manifest.entries.pop();
+            return Err(e);
+        }
+        if let Err(e) = move_path(path, &self.dir.join(&stored)) {
+            self.manifest.entries.pop();
+            // An archive with nothing in it would be the newest one `restore` picks.
+            if self.is_empty() {
+                remove_archive_dir(&self.dir)?;
+            } else {
+                self.save()?;
+            }
+            return Err(e).with_context(|| format!("Failed to archive {}", path.display()));
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = self.dir.join(MANIFEST_FILE);
+        let source =
+            serde_json::to_string_pretty(&self.manifest).context("Failed to serialize archive")?;
+        fs::write(&path, source).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.manifest.entries.is_empty()
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn tool(&self) -> &str {
+        &self.manifest.tool
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.manifest.entries
+    }
+
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(cache::now().saturating_sub(self.manifest.created_at))
+    }
+
+    /// Archives of `tool`, or of every tool, newest first.
+    pub fn list(tool: Option<&str>) -> Result<Vec<Self>> {
+        list_in(&Self::root()?, tool)
+    }
+
+    /// Moves every entry back to where it came from. Entries whose original location is taken
+    /// stay in the archive; the archive is deleted once it is empty.
+    pub fn restore(mut self) -> Result<Restored> {
+        let mut result = Restored::default();
+        let mut remaining = Vec::new();
+        for entry in self.manifest.entries.drain(..) {
+            let stored = self.dir.join(&entry.stored);
+            // Recorded, but never moved: the stash was interrupted before the move.
+            if stored.symlink_metadata().is_err() {
+                continue;
+            }
+            if entry.original.symlink_metadata().is_ok() {
+                result.conflicts.push(entry.original.clone());
+                remaining.push(entry);
+                continue;
+            }
+            if let Some(parent) = entry.original.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            move_path(&stored, &entry.original)
+                .with_context(|| format!("Failed to restore {}", entry.original.display()))?;
+            result.restored.push(entry.original);
+        }
+
+        self.manifest.entries = remaining;
+        if self.is_empty() {
+            remove_archive_dir(&self.dir)?;
+        } else {
+            self.save()?;
+        }
+        Ok(result)
+    }
+
+    /// Deletes archives older than `retention`, returning how many were removed.
+    pub fn purge(retention: Duration) -> Result<usize> {
+        purge_in(&Self::root()?, retention, cache::now())
+    }
+}
+
+fn list_in(root: &Path, tool: Option<&str>) -> Result<Vec<Archive>> {
+    let tool_dirs: Vec<PathBuf> = match tool {
+        Some(tool) => vec![root.join(tool)],
+        None => read_dir(root)?,
+    };
+
+    let mut archives = Vec::new();
+    for dir in tool_dirs
+        .iter()
+        .flat_map(|dir| read_dir(dir).unwrap_or_default())
+    {
+        let Ok(source) = fs::read_to_string(dir.join(MANIFEST_FILE)) else {
+            continue;
+        };
+        if let Ok(manifest) = serde_json::from_str(&source) {
+            archives.push(Archive { dir, manifest });
+        }
+    }
+    archives.sort_by_key(|archive| Reverse(archive.manifest.created_at));
+    Ok(archives)
+}
+
+fn purge_in(root: &Path, retention: Duration, now: u64) -> Result<usize> {
+    let cutoff = now.saturating_sub(retention.as_secs());
+    let mut purged = 0;
+    for archive in list_in(root, None)? {
+        if archive.manifest.created_at < cutoff {
+            remove_archive_dir(&archive.dir)?;
+            purged += 1;
+        }
+    }
+    Ok(purged)
+}
+
+/// Removes an archive directory, and its tool directory once no archives are left in it.
+fn remove_archive_dir(dir: &Path) -> Result<()> {
+    fs::remove_dir_all(dir).with_context(|| format!("Failed to remove {}", dir.display()))?;
+    if let Some(tool_dir) = dir.parent() {
+        let _ = fs::remove_dir(tool_dir);
+    }
+    Ok(())
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+    match fs::read_dir(dir) {
+        Ok(entries) => Ok(entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_dir())
+            .collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    }
+}
+
+/// Renames `from` to `to`, copying and deleting instead when they are on different filesystems.
+fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_recursive(from, to)?;
+    if from.symlink_metadata()?.is_dir() {
+        fs::remove_dir_all(from)
+    } else {
+        fs::remove_file(from)
+    }
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> io::Result<()> {
+    let metadata = from.symlink_metadata()?;
+    if metadata.is_symlink() {
+        #[cfg(unix)]
+        return std::os::unix::fs::symlink(fs::read_link(from)?, to);
+        #[cfg(not(unix))]
+        return fs::copy(from, to).map(|_| ());
+    }
+    if metadata.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        return Ok(());
+    }
+    fs::copy(from, to).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Archive, list_in, purge_in};
+    use std::{fs, time::Duration};
+
+    #[test]
+    fn it_restores_archived_files_to_their_original_place() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let home = root.join("home");
+        let config = home.join(".factory");
+        fs::create_dir_all(&config).unwrap();
+        fs::write(config.join("history.json"), "[]").unwrap();
+
+        let mut archive = Archive::create_in(&root.join("archive"), "droid", 1_000);
+        archive.stash(&config).unwrap();
+        assert!(!config.exists());
+
+        let archive = list_in(&root.join("archive"), Some("droid"))
+            .unwrap()
+            .pop()
+            .unwrap();
+        let restored = archive.restore().unwrap();
+        assert_eq!(restored.restored, vec![config.clone()]);
+        assert_eq!(
+            fs::read_to_string(config.join("history.json")).unwrap(),
+            "[]"
+        );
+        assert!(list_in(&root.join("archive"), None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_forgets_entries_whose_move_failed() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let mut archive = Archive::create_in(&root.join("archive"), "codex", 1_000);
+        assert!(archive.stash(&root.join("missing")).is_err());
+        assert!(archive.is_empty());
+
+        assert!(
+            list_in(&root.join("archive"), Some("codex"))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(!root.join("archive/codex").exists());
+    }
+
+    #[test]
+    fn it_purges_archives_past_retention() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let file = root.join("claude");
+        for created_at in [1_000, 90_000] {
+            fs::write(&file, "").unwrap();
+            let mut archive = Archive::create_in(&root.join("archive"), "claude", created_at);
+            archive.stash(&file).unwrap();
+        }
+
+        let purged = purge_in(&root.join("archive"), Duration::from_secs(86_400), 100_000).unwrap();
+        assert_eq!(purged, 1);
+        let left = list_in(&root.join("archive"), None).unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].manifest.created_at, 90_000);
+    }
+}
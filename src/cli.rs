@@ -1,14 +1,16 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+
+use crate::{
+    report::Format,
+    tools::{self, Channel, Tool},
+    versions::CacheMode,
+};
 
 #[derive(Parser)]
 #[command(name = "ai-cli-apps")]
 #[command(arg_required_else_help = false)]
 #[command(disable_version_flag = true)]
-#[command(
-    about = "Check and manage AI CLI tools versions",
-    long_about = "Check and manage AI CLI tools versions\n\nSupported tools:\n  Claude Code (claude)\n  Amp (amp)\n  Codex (codex)\n  Cursor (cursor)\n  Copilot CLI (copilot)\n  Kilo (kilo)\n  Gemini (gemini)\n  Cline (cline)\n  OpenCode (opencode)\n  Factory CLI (droid)",
-    version
-)]
+#[command(about = "Check and manage AI CLI tools versions", version)]
 pub struct Cli {
     /// Print version
     #[arg(short = 'v', long, action = clap::ArgAction::Version)]
@@ -18,29 +20,105 @@ pub struct Cli {
     pub command: Option<Commands>,
 }
 
+impl Cli {
+    /// Parses the arguments, listing the catalog's tools, custom ones included, in `--help`.
+    /// A catalog that fails to load leaves the list out; the command itself reports the error.
+    pub fn parse_with_catalog() -> Self {
+        let mut command = Self::command();
+        if let Ok(tools) = tools::catalog() {
+            command = command.long_about(long_about(&tools));
+        }
+        Self::from_arg_matches(&command.get_matches()).unwrap_or_else(|e| e.exit())
+    }
+}
+
+fn long_about(tools: &[Tool]) -> String {
+    let mut about = "Check and manage AI CLI tools versions\n\nSupported tools:".to_string();
+    for tool in tools {
+        about.push_str(&format!("\n  {} ({})", tool.name, tool.identifier));
+    }
+    about
+}
+
 #[derive(Subcommand)]
 pub enum Commands {
     /// Check latest versions available
-    Check,
+    Check {
+        /// Show where each installed binary was found and its raw version output
+        #[arg(long)]
+        verbose: bool,
+        #[command(flatten)]
+        report: ReportArgs,
+    },
+    /// List installed tools with a newer release (exit code 3 if any, 4 if a check failed)
+    Outdated {
+        #[command(flatten)]
+        report: ReportArgs,
+    },
     /// Upgrade AI CLI tools (optionally specify tool name, e.g., 'amp')
     Upgrade {
         /// Optional tool name to upgrade directly (e.g., 'amp')
         tool: Option<String>,
+        /// Upgrade every outdated tool that is not held, without prompting
+        #[arg(long, conflicts_with = "tool")]
+        all: bool,
+        /// Print what would be done without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Show installer scripts and ask before running them
+        #[arg(long)]
+        review: bool,
     },
     /// Update AI CLI tools (alias for upgrade)
     Update {
         /// Optional tool name to update directly (e.g., 'amp')
         tool: Option<String>,
+        /// Update every outdated tool that is not held, without prompting
+        #[arg(long, conflicts_with = "tool")]
+        all: bool,
+        /// Print what would be done without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Show installer scripts and ask before running them
+        #[arg(long)]
+        review: bool,
+    },
+    /// Go back to the version installed before the last upgrade or rollback
+    Rollback {
+        /// Tool to roll back (e.g., 'claude')
+        tool: String,
+    },
+    /// Hold a tool back from bulk upgrades (lists held tools when no tool is given)
+    Hold {
+        /// Tool to hold (e.g., 'claude')
+        tool: Option<String>,
+    },
+    /// Release a held tool so bulk upgrades include it again
+    Unhold {
+        /// Tool to release (e.g., 'claude')
+        tool: String,
     },
     /// Install AI CLI tools (optionally specify tool name, e.g., 'claude')
     Install {
         /// Optional tool name to install directly (e.g., 'claude')
         tool: Option<String>,
+        /// Print what would be done without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Show installer scripts and ask before running them
+        #[arg(long)]
+        review: bool,
     },
     /// Install AI CLI tools (alias for install)
     Add {
         /// Optional tool name to install directly (e.g., 'claude')
         tool: Option<String>,
+        /// Print what would be done without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Show installer scripts and ask before running them
+        #[arg(long)]
+        review: bool,
     },
     /// Uninstall AI CLI tools (optionally specify tool name, e.g., 'claude')
     Uninstall {
@@ -52,6 +130,9 @@ pub enum Commands {
         /// Skip all confirmation prompts
         #[arg(long)]
         force: bool,
+        /// Print what would be done without changing anything
+        #[arg(long)]
+        dry_run: bool,
     },
     /// Uninstall AI CLI tools (alias for uninstall)
     Remove {
@@ -63,7 +144,97 @@ pub enum Commands {
         /// Skip all confirmation prompts
         #[arg(long)]
         force: bool,
+        /// Print what would be done without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Put back the files of an uninstalled tool (lists archives when no tool is given)
+    Restore {
+        /// Tool to restore from its most recent uninstall (e.g., 'claude')
+        tool: Option<String>,
+    },
+    /// Show the log of installs, upgrades and uninstalls, newest first
+    History {
+        /// Only show operations on this tool (e.g., 'claude')
+        tool: Option<String>,
+    },
+    /// Remove extra copies of a tool installed more than once
+    Dedupe {
+        /// Tool to deduplicate (e.g., 'claude')
+        tool: String,
+        /// Keep the copy installed with this method (npm, brew, brew_cask, bootstrap or amp)
+        /// instead of the one that runs now
+        #[arg(long)]
+        keep: Option<String>,
+        /// Skip the confirmation prompt
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check the prerequisites of every install method and suggest fixes
+    Doctor {
+        /// Apply the fixes that need no root access; shell rc files only get the backed-up PATH block
+        #[arg(long)]
+        fix: bool,
     },
     /// List installed AI CLI tools (alias for default command)
-    List,
+    List {
+        #[command(flatten)]
+        report: ReportArgs,
+    },
+    /// Write the exact version of every installed tool to ai-cli.lock
+    Lock,
+    /// Install or upgrade tools to satisfy .ai-cli-tools.toml (or ai-cli.lock with --locked)
+    Sync {
+        /// Install, upgrade or downgrade every tool to its version in ai-cli.lock
+        #[arg(long)]
+        locked: bool,
+    },
+    /// Check installed tools against the constraints in .ai-cli-tools.toml
+    Verify,
+    /// Show or switch the release channel a tool follows
+    Channel {
+        /// Tool to show or switch (lists every tool when omitted)
+        tool: Option<String>,
+        /// Channel to follow from now on
+        #[arg(value_enum)]
+        channel: Option<Channel>,
+    },
+}
+
+/// Options shared by `list`, `check` and `outdated`.
+#[derive(Args, Clone, Copy, Default)]
+pub struct ReportArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    pub format: Format,
+    /// Look up latest versions again instead of using cached ones
+    #[arg(long, conflicts_with = "offline")]
+    pub refresh: bool,
+    /// Use only cached latest versions and show how old they are
+    #[arg(long)]
+    pub offline: bool,
+}
+
+impl ReportArgs {
+    pub fn cache_mode(&self) -> CacheMode {
+        if self.offline {
+            CacheMode::Offline
+        } else if self.refresh {
+            CacheMode::Refresh
+        } else {
+            CacheMode::Cached
+        }
+    }
+}
+
+impl Commands {
+    /// Options of the reporting commands; other commands get the defaults.
+    pub fn report(&self) -> ReportArgs {
+        match self {
+            Commands::List { report }
+            | Commands::Check { report, .. }
+            | Commands::Outdated { report } => *report,
+            _ => ReportArgs::default(),
+        }
+    }
 }
@@ -0,0 +1,404 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use colored::*;
+
+use crate::{
+    paths, shell,
+    tools::{self, InstallMethod, Tool, command_output, find_on_path},
+    version::Version,
+};
+
+/// Oldest Node.js release the npm-distributed tools run on.
+const MIN_NODE: &str = "18.0.0";
+
+/// Oldest npm release, the major that ships with Node.js 18.
+const MIN_NPM: &str = "8.0.0";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// One prerequisite and, when it is not met, how to resolve it.
+#[derive(Debug)]
+pub struct Check {
+    pub name: String,
+    pub status: Status,
+    pub detail: String,
+    pub hint: Option<String>,
+    pub fix: Option<Fix>,
+}
+
+impl Check {
+    fn new(name: &str, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+            hint: None,
+            fix: None,
+        }
+    }
+
+    fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+/// A change `doctor --fix` makes on its own: nothing that needs root or strands existing
+/// installs, and shell rc files only through the backed-up managed block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fix {
+    /// Add `~/.local/bin` to PATH in the shell rc files.
+    LocalBinOnPath,
+}
+
+impl Fix {
+    pub fn apply(&self) -> Result<()> {
+        match self {
+            Fix::LocalBinOnPath => {
+                let local_bin = shell::local_bin()?;
+                fs::create_dir_all(&local_bin)
+                    .with_context(|| format!("Failed to create {}", local_bin.display()))?;
+                for edit in shell::add_local_bin()? {
+                    println!("  {} Edited {}", "→".cyan(), edit);
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fix::LocalBinOnPath => write!(f, "add ~/.local/bin to PATH in your shell rc files"),
+        }
+    }
+}
+
+/// Checks everything the catalog's install methods rely on.
+pub fn run() -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let home = paths::home_dir().ok();
+    checks.push(match &home {
+        Some(home) => Check::new("HOME", Status::Pass, home.display().to_string()),
+        None => Check::new("HOME", Status::Fail, "not set")
+            .with_hint("Set HOME to your home directory; every installer writes below it"),
+    });
+
+    let tools = match tools::catalog() {
+        Ok(tools) => {
+            checks.push(Check::new(
+                "Tool catalog",
+                Status::Pass,
+                format!("{} tools", tools.len()),
+            ));
+            tools
+        }
+        Err(e) => {
+            checks.push(
+                Check::new("Tool catalog", Status::Fail, format!("{:#}", e))
+                    .with_hint("Fix or remove the file named above"),
+            );
+            return checks;
+        }
+    };
+
+    for program in ["bash", "curl", "npm", "brew"] {
+        checks.extend(program_check(&tools, program, &find_on_path));
+    }
+
+    if find_on_path("npm").is_some() {
+        checks.push(npm_check());
+        checks.push(node_check());
+        checks.extend(npm_prefix_check(home.as_deref()));
+    }
+
+    let uses_scripts = tools.iter().any(|tool| {
+        tool.install_methods
+            .iter()
+            .any(|method| matches!(method, InstallMethod::Bootstrap(_) | InstallMethod::Amp(_)))
+    });
+    if uses_scripts && let Some(home) = &home {
+        checks.push(local_bin_check(home));
+    }
+
+    checks
+}
+
+/// Programs `method` needs; installer scripts download their payload with curl.
+fn requirements(method: &InstallMethod) -> &'static [&'static str] {
+    match method {
+        InstallMethod::Npm(_) => &["npm"],
+        InstallMethod::Brew(_) | InstallMethod::BrewCask(_) => &["brew"],
+        InstallMethod::Bootstrap(_) | InstallMethod::Amp(_) => &["bash", "curl"],
+    }
+}
+
+/// A missing program fails only when some tool has no other install method left.
+fn program_check(
+    tools: &[Tool],
+    program: &str,
+    locate: &dyn Fn(&str) -> Option<PathBuf>,
+) -> Option<Check> {
+    let users: Vec<&Tool> = tools
+        .iter()
+        .filter(|tool| {
+            tool.install_methods
+                .iter()
+                .any(|method| requirements(method).contains(&program))
+        })
+        .collect();
+    if users.is_empty() {
+        return None;
+    }
+    if let Some(path) = locate(program) {
+        return Some(Check::new(
+            program,
+            Status::Pass,
+            path.display().to_string(),
+        ));
+    }
+
+    let usable = |method: &InstallMethod| {
+        requirements(method)
+            .iter()
+            .all(|needed| locate(needed).is_some())
+    };
+    let stuck: Vec<&str> = users
+        .iter()
+        .filter(|tool| !tool.install_methods.iter().any(usable))
+        .map(|tool| tool.name.as_str())
+        .collect();
+    let hint = match program {
+        "npm" => "Install Node.js 18 or newer from https://nodejs.org or your package manager",
+        "brew" => "Install Homebrew from https://brew.sh",
+        _ => "Install it with your system package manager",
+    };
+
+    let check = if stuck.is_empty() {
+        let names: Vec<&str> = users.iter().map(|tool| tool.name.as_str()).collect();
+        Check::new(
+            program,
+            Status::Warn,
+            format!("not found; {} use another install method", names.join(", ")),
+        )
+    } else {
+        Check::new(
+            program,
+            Status::Fail,
+            format!("not found; needed to install {}", stuck.join(", ")),
+        )
+    };
+    Some(check.with_hint(hint))
+}
+
+fn npm_check() -> Check {
+    let Some(output) = command_output("npm", &["--version"]) else {
+        return Check::new("npm version", Status::Warn, "`npm --version` failed")
+            .with_hint("Reinstall Node.js from https://nodejs.org or your package manager");
+    };
+    match npm_is_supported(&output) {
+        Some(true) => Check::new("npm version", Status::Pass, output),
+        Some(false) => Check::new(
+            "npm version",
+            Status::Warn,
+            format!("{} is older than {}", output, MIN_NPM),
+        )
+        .with_hint("Upgrade npm with `npm install -g npm@latest`; global installs may fail"),
+        None => Check::new(
+            "npm version",
+            Status::Warn,
+            format!("could not read version from `{}`", output),
+        ),
+    }
+}
+
+fn npm_is_supported(output: &str) -> Option<bool> {
+    let version = Version::parse(output)?;
+    Some(version >= Version::parse(MIN_NPM)?)
+}
+
+fn node_check() -> Check {
+    let Some(output) = command_output("node", &["--version"]) else {
+        return Check::new("node", Status::Fail, "npm is installed but node is not")
+            .with_hint("Reinstall Node.js from https://nodejs.org or your package manager");
+    };
+    match node_is_supported(&output) {
+        Some(true) => Check::new("node", Status::Pass, output),
+        Some(false) => Check::new(
+            "node",
+            Status::Warn,
+            format!("{} is older than {}", output, MIN_NODE),
+        )
+        .with_hint("Upgrade Node.js; npm-based tools may fail to start"),
+        None => Check::new(
+            "node",
+            Status::Warn,
+            format!("could not read version from `{}`", output),
+        ),
+    }
+}
+
+fn node_is_supported(output: &str) -> Option<bool> {
+    let version = Version::parse(output)?;
+    Some(version >= Version::parse(MIN_NODE)?)
+}
+
+/// Global installs into a root-owned prefix such as `/usr/local` need sudo. Moving the prefix
+/// would strand the packages already installed there, so it is only suggested.
+fn npm_prefix_check(home: Option<&Path>) -> Option<Check> {
+    let prefix = PathBuf::from(command_output("npm", &["prefix", "-g"])?);
+    let modules = prefix.join("lib").join("node_modules");
+    let target = if modules.is_dir() { &modules } else { &prefix };
+
+    if is_writable(target) {
+        return Some(Check::new(
+            "npm prefix",
+            Status::Pass,
+            prefix.display().to_string(),
+        ));
+    }
+
+    let check = Check::new(
+        "npm prefix",
+        Status::Warn,
+        format!(
+            "{} is not writable; global installs need sudo",
+            target.display()
+        ),
+    );
+    Some(match home {
+        Some(home) => {
+            let mut hint = format!(
+                "Use a user-owned prefix: `npm config set prefix {}`",
+                home.join(".local").display()
+            );
+            let stranded = global_packages(&modules);
+            if !stranded.is_empty() {
+                hint.push_str(&format!(
+                    ", then reinstall {}; they stay under the old prefix and drop off PATH",
+                    stranded.join(", ")
+                ));
+            }
+            check.with_hint(hint)
+        }
+        None => check,
+    })
+}
+
+/// Packages installed under a global `node_modules`, apart from the ones Node.js ships.
+fn global_packages(modules: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(modules) else {
+        return Vec::new();
+    };
+    let mut packages = Vec::new();
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('@') {
+            for scoped in fs::read_dir(entry.path()).into_iter().flatten().flatten() {
+                packages.push(format!("{}/{}", name, scoped.file_name().to_string_lossy()));
+            }
+        } else if !name.starts_with('.') && !matches!(name.as_str(), "npm" | "corepack") {
+            packages.push(name);
+        }
+    }
+    packages.sort();
+    packages
+}
+
+fn local_bin_check(home: &Path) -> Check {
+    let local_bin = home.join(".local").join("bin");
+    if shell::is_on_path(&local_bin) {
+        return Check::new("~/.local/bin", Status::Pass, "on PATH");
+    }
+    if shell::has_local_bin_block() {
+        return Check::new("~/.local/bin", Status::Warn, "not on PATH in this shell")
+            .with_hint("Your shell rc files already add it; open a new shell");
+    }
+    Check::new("~/.local/bin", Status::Warn, "not on PATH")
+        .with_hint("Installer scripts link binaries there; new shells need it on PATH")
+        .with_fix(Fix::LocalBinOnPath)
+}
+
+fn is_writable(dir: &Path) -> bool {
+    let probe = dir.join(format!(".ai-cli-apps-doctor-{}", std::process::id()));
+    match fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Status, node_is_supported, npm_is_supported, program_check};
+    use crate::tools::Tool;
+    use std::path::PathBuf;
+
+    fn tool(name: &str, install: &str) -> Tool {
+        toml::from_str(&format!(
+            "name = \"{name}\"\nidentifier = \"{name}\"\ninstall = {install}\ncheck = [\"{name}\"]"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn it_fails_only_when_no_install_method_is_left() {
+        let tools = [
+            tool(
+                "codex",
+                r#"[{ brew = "codex" }, { npm = "@openai/codex" }]"#,
+            ),
+            tool("opencode", r#"{ brew = "opencode" }"#),
+        ];
+        let with_npm = |program: &str| (program == "npm").then(|| PathBuf::from("/usr/bin/npm"));
+
+        let brew = program_check(&tools, "brew", &with_npm).unwrap();
+        assert_eq!(brew.status, Status::Fail);
+        assert!(brew.detail.contains("opencode"));
+        assert!(!brew.detail.contains("codex"));
+
+        let tools = &tools[..1];
+        assert_eq!(
+            program_check(tools, "brew", &with_npm).unwrap().status,
+            Status::Warn
+        );
+        assert_eq!(
+            program_check(tools, "npm", &with_npm).unwrap().status,
+            Status::Pass
+        );
+        assert!(program_check(tools, "curl", &with_npm).is_none());
+    }
+
+    #[test]
+    fn it_requires_node_18() {
+        assert_eq!(node_is_supported("v20.11.1"), Some(true));
+        assert_eq!(node_is_supported("v16.20.2"), Some(false));
+        assert_eq!(node_is_supported("unknown"), None);
+    }
+
+    #[test]
+    fn it_requires_npm_8() {
+        assert_eq!(npm_is_supported("10.8.2"), Some(true));
+        assert_eq!(npm_is_supported("8.0.0"), Some(true));
+        assert_eq!(npm_is_supported("6.14.18"), Some(false));
+        assert_eq!(npm_is_supported("unknown"), None);
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{cache, paths};
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Install,
+    Upgrade,
+    Uninstall,
+    Rollback,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Action::Install => "install",
+            Action::Upgrade => "upgrade",
+            Action::Uninstall => "uninstall",
+            Action::Rollback => "rollback",
+        })
+    }
+}
+
+/// One install, upgrade, uninstall or rollback, as a line of
+/// `~/.local/share/ai-cli-apps/history.jsonl`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Entry {
+    /// Unix time the operation finished.
+    pub timestamp: u64,
+    pub action: Action,
+    /// Tool identifier.
+    pub tool: String,
+    /// Install method kind, e.g. `npm` or `bootstrap`.
+    pub method: String,
+    pub version_before: Option<String>,
+    pub version_after: Option<String>,
+    pub commands: Vec<CommandRun>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// A command an operation ran. Installer scripts are shown as `bash <url> [args]`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommandRun {
+    pub command: String,
+    /// `None` when the command could not be started or was killed by a signal.
+    pub exit_code: Option<i32>,
+}
+
+impl Entry {
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(cache::now().saturating_sub(self.timestamp))
+    }
+}
+
+fn path() -> Result<PathBuf> {
+    Ok(paths::data_dir()?.join(HISTORY_FILE))
+}
+
+/// Appends `entry` to the history log.
+pub fn append(entry: &Entry) -> Result<()> {
+    append_to(&path()?, entry)
+}
+
+fn append_to(path: &Path, entry: &Entry) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let line = serde_json::to_string(entry).context("Failed to serialize history entry")?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    writeln!(file, "{}", line).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Logged operations on `tool`, or on every tool, oldest first.
+pub fn load(tool: Option<&str>) -> Result<Vec<Entry>> {
+    load_from(&path()?, tool)
+}
+
+fn load_from(path: &Path, tool: Option<&str>) -> Result<Vec<Entry>> {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    // A line cut short by an interrupted write is skipped rather than hiding the rest.
+    Ok(source
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Entry>(line).ok())
+        .filter(|entry| tool.is_none_or(|tool| entry.tool == tool))
+        .collect())
+}
+
+/// The version installed before the most recent successful upgrade or rollback that changed
+/// it, so rolling back right after a rollback returns to the version that was rolled back.
+pub fn version_before_last_upgrade(entries: &[Entry]) -> Option<&str> {
+    entries
+        .iter()
+        .rev()
+        .filter(|entry| matches!(entry.action, Action::Upgrade | Action::Rollback) && entry.success)
+        .find_map(
+            |entry| match (&entry.version_before, &entry.version_after) {
+                (Some(before), Some(after)) if before != after => Some(before.as_str()),
+                _ => None,
+            },
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Action, CommandRun, Entry, append_to, load_from, version_before_last_upgrade};
+    use std::fs;
+
+    fn entry(tool: &str, action: Action) -> Entry {
+        Entry {
+            timestamp: 1_000,
+            action,
+            tool: tool.to_string(),
+            method: "npm".to_string(),
+            version_before: None,
+            version_after: Some("1.0.0".to_string()),
+            commands: vec![CommandRun {
+                command: format!("npm install -g {}", tool),
+                exit_code: Some(0),
+            }],
+            success: true,
+            error: None,
+            duration_ms: 1_500,
+        }
+    }
+
+    #[test]
+    fn it_appends_and_filters_history_by_tool() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let path = dir.join("history.jsonl");
+        append_to(&path, &entry("codex", Action::Install)).unwrap();
+        append_to(&path, &entry("gemini", Action::Install)).unwrap();
+        fs::write(
+            &path,
+            fs::read_to_string(&path).unwrap() + "{\"timestamp\":\n",
+        )
+        .unwrap();
+        append_to(&path, &entry("codex", Action::Upgrade)).unwrap();
+
+        assert_eq!(load_from(&path, None).unwrap().len(), 3);
+        let codex = load_from(&path, Some("codex")).unwrap();
+        let actions: Vec<Action> = codex.iter().map(|entry| entry.action).collect();
+        assert_eq!(actions, vec![Action::Install, Action::Upgrade]);
+    }
+
+    #[test]
+    fn it_finds_the_version_before_the_last_upgrade() {
+        let upgrade = |before: &str, after: &str, success: bool| Entry {
+            version_before: Some(before.to_string()),
+            version_after: Some(after.to_string()),
+            success,
+            ..entry("claude", Action::Upgrade)
+        };
+        let entries = [
+            entry("claude", Action::Install),
+            upgrade("2.0.1", "2.0.5", true),
+            upgrade("2.0.5", "2.0.5", true),
+            upgrade("2.0.5", "2.0.5", false),
+        ];
+        assert_eq!(version_before_last_upgrade(&entries), Some("2.0.1"));
+        assert_eq!(version_before_last_upgrade(&entries[..1]), None);
+    }
+
+    #[test]
+    fn it_treats_rollbacks_as_version_changes() {
+        let change = |action: Action, before: &str, after: &str| Entry {
+            version_before: Some(before.to_string()),
+            version_after: Some(after.to_string()),
+            ..entry("claude", action)
+        };
+        let entries = [
+            change(Action::Upgrade, "2.0.1", "2.0.5"),
+            change(Action::Rollback, "2.0.5", "2.0.1"),
+            change(Action::Upgrade, "2.0.1", "2.0.8"),
+        ];
+        assert_eq!(version_before_last_upgrade(&entries[..2]), Some("2.0.5"));
+        assert_eq!(version_before_last_upgrade(&entries), Some("2.0.1"));
+    }
+}
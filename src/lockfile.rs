@@ -0,0 +1,144 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::project::{PROJECT_MANIFEST, find_upwards};
+
+pub const LOCKFILE: &str = "ai-cli.lock";
+
+const HEADER: &str =
+    "# Generated by `ai-cli-apps lock`. Apply with `ai-cli-apps sync --locked`.\n\n";
+
+/// Exact tool versions captured by `ai-cli-apps lock`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Lockfile {
+    #[serde(default, rename = "tool")]
+    pub tools: Vec<LockedTool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LockedTool {
+    pub identifier: String,
+    pub version: String,
+    /// Install method kind: `npm`, `brew`, `bootstrap` or `amp`.
+    pub method: String,
+    /// npm package, Homebrew formula or installer script URL.
+    pub source: String,
+    /// SHA-256 of the bootstrap script fetched when the lock was written. `lock` downloads the
+    /// script again rather than reading what the version was installed with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script_sha256: Option<String>,
+}
+
+impl Lockfile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = fs::read_to_string(path).with_context(|| {
+            format!(
+                "Failed to read {}. Run `ai-cli-apps lock` to create it.",
+                path.display()
+            )
+        })?;
+        toml::from_str(&source).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let source = toml::to_string_pretty(self).context("Failed to serialize lockfile")?;
+        fs::write(path, format!("{}{}", HEADER, source))
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// The `ai-cli.lock` in the current directory or the nearest parent, found the same way as
+/// `.ai-cli-tools.toml`.
+pub fn discover() -> Result<PathBuf> {
+    let cwd = env::current_dir().context("Failed to read the current directory")?;
+    find_upwards(&cwd, LOCKFILE).with_context(|| {
+        format!(
+            "No {} found in {} or any parent directory. Run `ai-cli-apps lock` to create it.",
+            LOCKFILE,
+            cwd.display()
+        )
+    })
+}
+
+/// Where `lock` writes: over the lockfile `sync --locked` would read, else beside the project
+/// manifest, else in the current directory.
+pub fn target() -> Result<PathBuf> {
+    let cwd = env::current_dir().context("Failed to read the current directory")?;
+    Ok(target_from(&cwd))
+}
+
+fn target_from(start: &Path) -> PathBuf {
+    find_upwards(start, LOCKFILE)
+        .or_else(|| {
+            find_upwards(start, PROJECT_MANIFEST)
+                .and_then(|manifest| manifest.parent().map(|dir| dir.join(LOCKFILE)))
+        })
+        .unwrap_or_else(|| start.join(LOCKFILE))
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LOCKFILE, LockedTool, Lockfile, sha256_hex, target_from};
+    use crate::project::PROJECT_MANIFEST;
+    use std::fs;
+
+    #[test]
+    fn it_round_trips_locked_tools() {
+        let lockfile = Lockfile {
+            tools: vec![
+                LockedTool {
+                    identifier: "claude".to_string(),
+                    version: "2.0.31".to_string(),
+                    method: "bootstrap".to_string(),
+                    source: "https://example.com/bootstrap.sh".to_string(),
+                    script_sha256: Some(sha256_hex(b"abc")),
+                },
+                LockedTool {
+                    identifier: "copilot".to_string(),
+                    version: "0.0.350".to_string(),
+                    method: "npm".to_string(),
+                    source: "@github/copilot".to_string(),
+                    script_sha256: None,
+                },
+            ],
+        };
+
+        let source = toml::to_string_pretty(&lockfile).unwrap();
+        assert!(source.contains("[[tool]]"), "{}", source);
+        let parsed: Lockfile = toml::from_str(&source).unwrap();
+        assert_eq!(parsed.tools, lockfile.tools);
+    }
+
+    #[test]
+    fn it_writes_the_lockfile_beside_the_project_manifest() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let nested = root.join("crates/app");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(target_from(&nested), nested.join(LOCKFILE));
+
+        fs::write(root.join(PROJECT_MANIFEST), "[tools]\n").unwrap();
+        assert_eq!(target_from(&nested), root.join(LOCKFILE));
+
+        fs::write(nested.join(LOCKFILE), "").unwrap();
+        assert_eq!(target_from(&nested), nested.join(LOCKFILE));
+    }
+
+    #[test]
+    fn it_hashes_scripts_as_lowercase_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -1,38 +1,70 @@
 mod actions;
+mod archive;
+mod cache;
 mod cli;
+mod doctor;
+mod history;
+mod lockfile;
+mod paths;
+mod plan;
+mod project;
+mod report;
+mod settings;
+mod shell;
 mod tools;
+mod trust;
+mod version;
 mod versions;
 
-use actions::{handle_install_command, handle_uninstall_command, handle_upgrade_command};
+use actions::{
+    handle_channel_command, handle_dedupe_command, handle_doctor_command, handle_history_command,
+    handle_hold_command, handle_install_command, handle_lock_command, handle_restore_command,
+    handle_rollback_command, handle_sync_command, handle_uninstall_command, handle_upgrade_command,
+    handle_verify_command,
+};
 use anyhow::Result;
-use clap::Parser;
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, ReportArgs};
 use colored::*;
 use indicatif::{ProgressBar, ProgressStyle};
+use report::Format;
+use std::io;
 use tools::installed_versions;
-use versions::{check_latest_versions, print_version};
+use versions::{
+    UpdateStatus, check_latest_versions, print_copies, print_details, print_version, update_status,
+};
+
+/// `outdated` exit code when at least one installed tool has a newer release.
+const EXIT_OUTDATED: i32 = 3;
+/// `outdated` exit code when nothing is outdated but a latest version could not be determined.
+const EXIT_UNKNOWN: i32 = 4;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
-    println!("\n{}", "🤖 AI Tools Manager".bright_cyan().bold());
-    println!("{}\n", "=".repeat(19).bright_cyan());
+    let cli = Cli::parse_with_catalog();
+    let report = cli
+        .command
+        .as_ref()
+        .map_or_else(ReportArgs::default, Commands::report);
+    let format = report.format;
+    let cache_mode = report.cache_mode();
+    let text = format == Format::Text;
+    let mut exit_code = 0;
+    if text {
+        println!("\n{}", "🤖 AI Tools Manager".bright_cyan().bold());
+        println!("{}\n", "=".repeat(19).bright_cyan());
+    }
 
     match cli.command {
-        None | Some(Commands::List) => {
-            let spinner = ProgressBar::new_spinner();
-            spinner.set_style(
-                ProgressStyle::default_spinner()
-                    .template("{spinner:.cyan} {msg}")
-                    .unwrap(),
-            );
-            spinner.enable_steady_tick(std::time::Duration::from_millis(80));
-            spinner.set_message("Checking installed tools...");
-
-            let mut tools = installed_versions();
+        None | Some(Commands::List { .. }) => {
+            let spinner = spinner(text);
+            let mut tools = installed_versions()?;
             spinner.finish_and_clear();
 
-            check_latest_versions(&mut tools).await;
+            check_latest_versions(&mut tools, text, cache_mode).await;
+            if !text {
+                report::write(format, &tools, io::stdout().lock())?;
+                return Ok(());
+            }
 
             let label_width = tools.iter().map(|t| t.name.len()).max().unwrap_or(0);
             let id_width = tools
@@ -43,13 +75,9 @@ async fn main() -> Result<()> {
             let installed: Vec<_> = tools.iter().filter(|t| t.installed.is_some()).collect();
             let not_installed: Vec<_> = tools.iter().filter(|t| t.installed.is_none()).collect();
 
-            let all_up_to_date = installed.iter().all(|t| {
-                if let (Some(installed_ver), Some(latest_ver)) = (&t.installed, &t.latest) {
-                    installed_ver.contains(latest_ver) || latest_ver.contains(installed_ver)
-                } else {
-                    true
-                }
-            });
+            let all_up_to_date = installed
+                .iter()
+                .all(|t| update_status(t) != UpdateStatus::Outdated);
 
             if !installed.is_empty() {
                 println!("{}", "Installed:".bright_green().bold());
@@ -59,6 +87,15 @@ async fn main() -> Result<()> {
                 if all_up_to_date {
                     println!("\n{}", "✓ All tools are up to date".green());
                 }
+
+                let duplicated: Vec<_> =
+                    installed.iter().filter(|t| !t.copies.is_empty()).collect();
+                if !duplicated.is_empty() {
+                    println!();
+                    for tool in duplicated {
+                        print_copies(tool);
+                    }
+                }
             }
 
             if !not_installed.is_empty() {
@@ -71,20 +108,17 @@ async fn main() -> Result<()> {
                 }
             }
         }
-        Some(Commands::Check) => {
-            let spinner = ProgressBar::new_spinner();
-            spinner.set_style(
-                ProgressStyle::default_spinner()
-                    .template("{spinner:.cyan} {msg}")
-                    .unwrap(),
-            );
-            spinner.enable_steady_tick(std::time::Duration::from_millis(80));
-            spinner.set_message("Checking installed tools...");
-
-            let mut tools = installed_versions();
+        Some(Commands::Check { verbose, .. }) => {
+            let spinner = spinner(text);
+            let mut tools = installed_versions()?;
             spinner.finish_and_clear();
 
-            check_latest_versions(&mut tools).await;
+            check_latest_versions(&mut tools, text, cache_mode).await;
+            if !text {
+                report::write(format, &tools, io::stdout().lock())?;
+                return Ok(());
+            }
+
             let label_width = tools.iter().map(|t| t.name.len()).max().unwrap_or(0);
             let id_width = tools
                 .iter()
@@ -94,28 +128,174 @@ async fn main() -> Result<()> {
             println!();
             for tool in &tools {
                 print_version(tool, true, label_width, id_width);
+                if verbose {
+                    print_details(tool);
+                }
+            }
+        }
+        Some(Commands::Outdated { .. }) => {
+            let spinner = spinner(text);
+            let mut tools = installed_versions()?;
+            tools.retain(|t| t.installed.is_some());
+            spinner.finish_and_clear();
+
+            check_latest_versions(&mut tools, text, cache_mode).await;
+            let outdated: Vec<_> = tools
+                .iter()
+                .filter(|t| update_status(t) == UpdateStatus::Outdated)
+                .collect();
+            let unknown: Vec<_> = tools
+                .iter()
+                .filter(|t| update_status(t) == UpdateStatus::Unknown)
+                .collect();
+
+            if text {
+                let label_width = outdated.iter().map(|t| t.name.len()).max().unwrap_or(0);
+                let id_width = outdated
+                    .iter()
+                    .map(|t| t.identifier.as_ref().map(|id| id.len()).unwrap_or(0))
+                    .max()
+                    .unwrap_or(0);
+                for tool in &outdated {
+                    print_version(tool, true, label_width, id_width);
+                }
+                for tool in &unknown {
+                    eprintln!("{} {}: latest version unknown", "?".yellow(), tool.name);
+                }
+                if outdated.is_empty() && unknown.is_empty() {
+                    println!("{}", "✓ All tools are up to date".green());
+                }
+            } else {
+                // Unknown tools are included so scripts can see why the exit code is 4.
+                let records = outdated.iter().chain(&unknown).copied();
+                report::write(format, records, io::stdout().lock())?;
             }
+
+            exit_code = outdated_exit_code(tools.iter().map(update_status));
+        }
+        Some(Commands::Upgrade {
+            tool,
+            all,
+            dry_run,
+            review,
+        })
+        | Some(Commands::Update {
+            tool,
+            all,
+            dry_run,
+            review,
+        }) => {
+            handle_upgrade_command(tool.as_deref(), all, dry_run, review).await?;
         }
-        Some(Commands::Upgrade { tool }) | Some(Commands::Update { tool }) => {
-            handle_upgrade_command(tool.as_deref()).await?;
+        Some(Commands::Rollback { tool }) => {
+            handle_rollback_command(&tool).await?;
         }
-        Some(Commands::Install { tool }) | Some(Commands::Add { tool }) => {
-            handle_install_command(tool.as_deref()).await?;
+        Some(Commands::Hold { tool }) => {
+            handle_hold_command(tool.as_deref(), false)?;
+        }
+        Some(Commands::Unhold { tool }) => {
+            handle_hold_command(Some(&tool), true)?;
+        }
+        Some(Commands::Install {
+            tool,
+            dry_run,
+            review,
+        })
+        | Some(Commands::Add {
+            tool,
+            dry_run,
+            review,
+        }) => {
+            handle_install_command(tool.as_deref(), dry_run, review).await?;
         }
         Some(Commands::Uninstall {
             tool,
             remove_config,
             force,
+            dry_run,
         })
         | Some(Commands::Remove {
             tool,
             remove_config,
             force,
+            dry_run,
         }) => {
-            handle_uninstall_command(tool.as_deref(), remove_config, force).await?;
+            handle_uninstall_command(tool.as_deref(), remove_config, force, dry_run).await?;
+        }
+        Some(Commands::History { tool }) => {
+            handle_history_command(tool.as_deref())?;
+        }
+        Some(Commands::Dedupe { tool, keep, force }) => {
+            handle_dedupe_command(&tool, keep.as_deref(), force).await?;
+        }
+        Some(Commands::Doctor { fix }) => {
+            handle_doctor_command(fix)?;
+        }
+        Some(Commands::Restore { tool }) => {
+            handle_restore_command(tool.as_deref())?;
+        }
+        Some(Commands::Lock) => {
+            handle_lock_command().await?;
+        }
+        Some(Commands::Sync { locked }) => {
+            handle_sync_command(locked).await?;
+        }
+        Some(Commands::Verify) => {
+            handle_verify_command()?;
+        }
+        Some(Commands::Channel { tool, channel }) => {
+            handle_channel_command(tool.as_deref(), channel)?;
         }
     }
 
-    println!();
+    if text {
+        println!();
+    }
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
     Ok(())
 }
+
+/// Exit code of `outdated`: an outdated tool outranks one whose latest version is unknown.
+fn outdated_exit_code(statuses: impl IntoIterator<Item = UpdateStatus>) -> i32 {
+    let mut exit_code = 0;
+    for status in statuses {
+        match status {
+            UpdateStatus::Outdated => return EXIT_OUTDATED,
+            UpdateStatus::Unknown => exit_code = EXIT_UNKNOWN,
+            UpdateStatus::UpToDate | UpdateStatus::NotInstalled => {}
+        }
+    }
+    exit_code
+}
+
+fn spinner(visible: bool) -> ProgressBar {
+    if !visible {
+        return ProgressBar::hidden();
+    }
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(
+        ProgressStyle::default_spinner()
+            .template("{spinner:.cyan} {msg}")
+            .unwrap(),
+    );
+    spinner.enable_steady_tick(std::time::Duration::from_millis(80));
+    spinner.set_message("Checking installed tools...");
+    spinner
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EXIT_OUTDATED, EXIT_UNKNOWN, outdated_exit_code};
+    use crate::versions::UpdateStatus::{NotInstalled, Outdated, Unknown, UpToDate};
+
+    #[test]
+    fn it_maps_outdated_statuses_to_exit_codes() {
+        assert_eq!(outdated_exit_code([]), 0);
+        assert_eq!(outdated_exit_code([UpToDate, NotInstalled]), 0);
+        assert_eq!(outdated_exit_code([UpToDate, Unknown]), EXIT_UNKNOWN);
+        assert_eq!(outdated_exit_code([Unknown, Outdated]), EXIT_OUTDATED);
+        assert_eq!(outdated_exit_code([Outdated, Unknown]), EXIT_OUTDATED);
+    }
+}
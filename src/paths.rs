@@ -0,0 +1,39 @@
+use std::{env, path::PathBuf};
+
+use anyhow::{Context, Result};
+
+const APP_DIR: &str = "ai-cli-apps";
+
+pub fn home_dir() -> Result<PathBuf> {
+    env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .map(PathBuf::from)
+        .context("HOME environment variable not set")
+}
+
+/// `$XDG_CONFIG_HOME/ai-cli-apps`, falling back to `~/.config/ai-cli-apps`.
+pub fn config_dir() -> Result<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", &[".config"])
+}
+
+/// `$XDG_CACHE_HOME/ai-cli-apps`, falling back to `~/.cache/ai-cli-apps`.
+pub fn cache_dir() -> Result<PathBuf> {
+    xdg_dir("XDG_CACHE_HOME", &[".cache"])
+}
+
+/// `$XDG_DATA_HOME/ai-cli-apps`, falling back to `~/.local/share/ai-cli-apps`.
+pub fn data_dir() -> Result<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", &[".local", "share"])
+}
+
+fn xdg_dir(variable: &str, fallback: &[&str]) -> Result<PathBuf> {
+    if let Ok(dir) = env::var(variable)
+        && !dir.is_empty()
+    {
+        return Ok(PathBuf::from(dir).join(APP_DIR));
+    }
+
+    let mut dir = home_dir()?;
+    dir.extend(fallback);
+    Ok(dir.join(APP_DIR))
+}
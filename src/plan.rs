@@ -0,0 +1,191 @@
+use std::{fmt, path::PathBuf};
+
+use colored::*;
+
+/// What an install, upgrade or uninstall is going to do, worked out before anything runs so
+/// `--dry-run` can print it instead.
+#[derive(Debug, Default)]
+pub struct Plan {
+    pub steps: Vec<Step>,
+    /// Things worth knowing that are not steps, e.g. config directories that are kept.
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// Download an installer script and run it with bash, refusing it when a locked digest
+    /// no longer matches.
+    RunScript {
+        url: String,
+        description: String,
+        args: Vec<String>,
+        sha256: Option<String>,
+        /// Page through the script and ask before running it, even when it is approved.
+        review: bool,
+    },
+    /// Run a command; a failing `may_fail` command does not stop the plan.
+    Run {
+        command: Vec<String>,
+        may_fail: bool,
+    },
+    /// Move a binary or installer directory into the uninstall archive.
+    Archive { label: &'static str, path: PathBuf },
+    /// Move a config directory into the uninstall archive once the user agrees.
+    ArchiveConfig(PathBuf),
+    /// Point a symlink at a version an installer kept, e.g. in `~/.local/share/claude/versions`.
+    Link { link: PathBuf, target: PathBuf },
+    /// Run the tool's check command; an upgrade it fails for is rolled back when `rollback` is
+    /// set, i.e. when the current version can be installed or relinked again.
+    Verify { check: Vec<String>, rollback: bool },
+    /// Add `~/.local/bin` to PATH in the shell rc files.
+    AddLocalBinToPath,
+    /// Remove an installer's `# marker` PATH lines, and the managed PATH block once
+    /// `~/.local/bin` is empty.
+    CleanUpShellRc {
+        installer_marker: Option<&'static str>,
+    },
+}
+
+impl Step {
+    pub fn run(command: &[&str]) -> Self {
+        Step::Run {
+            command: command.iter().map(|arg| arg.to_string()).collect(),
+            may_fail: false,
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::RunScript {
+                url,
+                description,
+                args,
+                sha256,
+                review,
+            } => {
+                write!(f, "Download the {} from {}", description, url)?;
+                if *review {
+                    write!(f, ", show it for approval,")?;
+                }
+                write!(f, " and run it with bash")?;
+                if !args.is_empty() {
+                    write!(f, " {}", args.join(" "))?;
+                }
+                if let Some(sha256) = sha256 {
+                    write!(f, " if its sha256 is {}", sha256)?;
+                }
+                Ok(())
+            }
+            Step::Run { command, may_fail } => {
+                write!(f, "Run `{}`", command.join(" "))?;
+                if *may_fail {
+                    write!(f, " (a failure is ignored)")?;
+                }
+                Ok(())
+            }
+            Step::Archive { label, path } => {
+                write!(
+                    f,
+                    "Move {} {} to the uninstall archive",
+                    label,
+                    path.display()
+                )
+            }
+            Step::ArchiveConfig(path) => write!(
+                f,
+                "Move config {} to the uninstall archive (asks first unless --force)",
+                path.display()
+            ),
+            Step::Link { link, target } => {
+                write!(f, "Point {} at {}", link.display(), target.display())
+            }
+            Step::Verify {
+                check,
+                rollback: true,
+            } => write!(
+                f,
+                "Run `{}` and roll back to the current version if it fails",
+                check.join(" ")
+            ),
+            Step::Verify {
+                check,
+                rollback: false,
+            } => write!(f, "Run `{}` to check the upgrade", check.join(" ")),
+            Step::AddLocalBinToPath => write!(f, "Add ~/.local/bin to PATH in your shell rc files"),
+            Step::CleanUpShellRc {
+                installer_marker: Some(marker),
+            } => write!(
+                f,
+                "Remove `# {}` PATH lines, and the ai-cli-apps PATH block once ~/.local/bin is empty, from your shell rc files",
+                marker
+            ),
+            Step::CleanUpShellRc {
+                installer_marker: None,
+            } => write!(
+                f,
+                "Remove the ai-cli-apps PATH block from your shell rc files once ~/.local/bin is empty"
+            ),
+        }
+    }
+}
+
+impl Plan {
+    pub fn push(&mut self, step: Step) {
+        self.steps.push(step);
+    }
+
+    /// Makes every installer script wait for the user to read and approve it.
+    pub fn review_scripts(&mut self) {
+        for step in &mut self.steps {
+            if let Step::RunScript { review, .. } = step {
+                *review = true;
+            }
+        }
+    }
+
+    /// Prints the steps `action` would take, for `--dry-run`.
+    pub fn print(&self, action: &str) {
+        println!("{} {}", "Dry run:".yellow().bold(), action);
+        for note in &self.notes {
+            println!("{} {}", "→".cyan(), note);
+        }
+        if self.steps.is_empty() {
+            println!("  Nothing to do");
+        }
+        for (index, step) in self.steps.iter().enumerate() {
+            println!("  {}. {}", index + 1, step);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Step;
+
+    #[test]
+    fn it_describes_each_step_for_review() {
+        let script = Step::RunScript {
+            url: "https://example.com/bootstrap.sh".to_string(),
+            description: "bootstrap script".to_string(),
+            args: vec!["1.2.3".to_string()],
+            sha256: Some("abc123".to_string()),
+            review: false,
+        };
+        assert_eq!(
+            script.to_string(),
+            "Download the bootstrap script from https://example.com/bootstrap.sh and run it with bash 1.2.3 if its sha256 is abc123"
+        );
+
+        let mut unlink = Step::run(&["brew", "unlink", "gemini-cli"]);
+        assert_eq!(unlink.to_string(), "Run `brew unlink gemini-cli`");
+        if let Step::Run { may_fail, .. } = &mut unlink {
+            *may_fail = true;
+        }
+        assert_eq!(
+            unlink.to_string(),
+            "Run `brew unlink gemini-cli` (a failure is ignored)"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::version::VersionReq;
+
+pub const PROJECT_MANIFEST: &str = ".ai-cli-tools.toml";
+
+/// Tools a repository requires, read from `.ai-cli-tools.toml`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectManifest {
+    /// Version constraints keyed by tool name or identifier.
+    #[serde(default)]
+    pub tools: BTreeMap<String, VersionReq>,
+}
+
+impl ProjectManifest {
+    /// Parses a manifest, rejecting one that would check nothing, such as constraints written
+    /// at the top level instead of under `[tools]`.
+    pub fn parse(source: &str) -> Result<Self> {
+        let manifest: Self = toml::from_str(source)
+            .context("Invalid project manifest; list constraints under [tools]")?;
+        if manifest.tools.is_empty() {
+            anyhow::bail!("Project manifest declares no tools; list constraints under [tools]");
+        }
+        Ok(manifest)
+    }
+
+    /// Finds `.ai-cli-tools.toml` in the current directory or the nearest parent.
+    pub fn discover() -> Result<(PathBuf, Self)> {
+        let cwd = env::current_dir().context("Failed to read the current directory")?;
+        let path = find_upwards(&cwd, PROJECT_MANIFEST).with_context(|| {
+            format!(
+                "No {} found in {} or any parent directory",
+                PROJECT_MANIFEST,
+                cwd.display()
+            )
+        })?;
+        let source = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let manifest =
+            Self::parse(&source).with_context(|| format!("Failed to load {}", path.display()))?;
+        Ok((path, manifest))
+    }
+}
+
+/// `name` in `start` or the nearest parent directory that has it.
+pub fn find_upwards(start: &Path, name: &str) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PROJECT_MANIFEST, ProjectManifest, find_upwards};
+    use crate::version::Version;
+    use std::fs;
+
+    #[test]
+    fn it_parses_tool_constraints() {
+        let manifest = ProjectManifest::parse(
+            r#"
+            [tools]
+            claude = ">=1.0.80"
+            codex = "~0.50"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.tools.len(), 2);
+        let codex = &manifest.tools["codex"];
+        assert!(codex.matches(&Version::parse("0.50.2").unwrap()));
+        assert!(!codex.matches(&Version::parse("0.51.0").unwrap()));
+
+        let err = format!(
+            "{:#}",
+            ProjectManifest::parse("[tools]\nclaude = \"newest\"").unwrap_err()
+        );
+        assert!(err.contains("invalid version"), "{}", err);
+    }
+
+    #[test]
+    fn it_rejects_a_manifest_that_checks_nothing() {
+        let top_level = format!(
+            "{:#}",
+            ProjectManifest::parse("claude = \">=1.0.80\"\n").unwrap_err()
+        );
+        assert!(
+            top_level.contains("unknown field `claude`"),
+            "{}",
+            top_level
+        );
+
+        let empty = format!("{:#}", ProjectManifest::parse("[tools]\n").unwrap_err());
+        assert!(empty.contains("declares no tools"), "{}", empty);
+    }
+
+    #[test]
+    fn it_discovers_the_manifest_in_a_parent_directory() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let nested = root.join("crates/app");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(PROJECT_MANIFEST), "[tools]\n").unwrap();
+
+        assert_eq!(
+            find_upwards(&nested, PROJECT_MANIFEST),
+            Some(root.join(PROJECT_MANIFEST))
+        );
+    }
+}
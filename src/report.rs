@@ -0,0 +1,227 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{
+    tools::ToolVersion,
+    versions::{UpdateStatus, update_status},
+};
+
+/// Version of the JSON/YAML document layout described in `docs/output-format.md`.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// How `list`, `check` and `outdated` print their results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Colored, human-readable text
+    #[default]
+    Text,
+    Json,
+    Yaml,
+    Csv,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    schema_version: u32,
+    tools: &'a [Record],
+}
+
+/// One tool in machine-readable output.
+#[derive(Debug, Serialize)]
+pub struct Record {
+    pub name: String,
+    pub identifier: String,
+    pub installed_version: Option<String>,
+    pub latest_version: Option<String>,
+    pub install_method: Option<&'static str>,
+    pub binary_path: Option<String>,
+    pub status: UpdateStatus,
+    /// Where the installed binary came from, e.g. `npm @google/gemini-cli`.
+    pub origin: Option<String>,
+}
+
+impl From<&ToolVersion> for Record {
+    fn from(tool: &ToolVersion) -> Self {
+        let installed = tool.installed.as_ref();
+        Self {
+            name: tool.name.clone(),
+            identifier: tool.identifier.clone().unwrap_or_else(|| tool.name.clone()),
+            installed_version: installed.map(|i| i.version.clone()),
+            latest_version: tool.latest.clone(),
+            install_method: tool.install_method.as_ref().map(|m| m.kind()),
+            binary_path: installed
+                .and_then(|i| i.binary_path.as_ref())
+                .map(|path| path.display().to_string()),
+            status: update_status(tool),
+            origin: installed
+                .and_then(|i| i.origin.as_ref())
+                .map(ToString::to_string),
+        }
+    }
+}
+
+/// Writes `tools` in a machine-readable format; JSON and YAML wrap them in a versioned document.
+pub fn write<'a>(
+    format: Format,
+    tools: impl IntoIterator<Item = &'a ToolVersion>,
+    mut out: impl Write,
+) -> Result<()> {
+    let records: Vec<Record> = tools.into_iter().map(Record::from).collect();
+    let report = Report {
+        schema_version: SCHEMA_VERSION,
+        tools: &records,
+    };
+
+    match format {
+        Format::Text => unreachable!("text output is printed by the caller"),
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, &report).context("Failed to write JSON")?;
+            writeln!(out)?;
+        }
+        Format::Yaml => {
+            serde_yaml_ng::to_writer(&mut out, &report).context("Failed to write YAML")?;
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            for record in &records {
+                writer.serialize(record).context("Failed to write CSV")?;
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Format, write};
+    use crate::tools::{Detection, InstallMethod, InstalledVersion, Origin, ToolVersion};
+    use crate::version::Version;
+    use std::path::PathBuf;
+
+    fn tools() -> Vec<ToolVersion> {
+        let installed = InstalledVersion {
+            version: "2.0.30".to_string(),
+            parsed: Version::parse("2.0.30"),
+            components: Vec::new(),
+            raw_output: "2.0.30 (Claude Code)".to_string(),
+            binary_path: Some(PathBuf::from("/usr/local/bin/claude")),
+            detection: Detection::Path,
+            origin: Some(Origin::Script),
+        };
+        let mut claude = ToolVersion::new("Claude Code")
+            .with_identifier("claude")
+            .with_installed(Some(installed))
+            .with_install_method(InstallMethod::Bootstrap("https://example.com".to_string()));
+        claude.latest = Some("2.0.31".to_string());
+
+        let copilot = ToolVersion::new("Copilot CLI")
+            .with_identifier("copilot")
+            .with_install_method(InstallMethod::Npm("@github/copilot".to_string()));
+        vec![claude, copilot]
+    }
+
+    fn render(format: Format) -> String {
+        let mut out = Vec::new();
+        write(format, &tools(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn it_writes_versioned_json_records() {
+        let json: serde_json::Value = serde_json::from_str(&render(Format::Json)).unwrap();
+        assert_eq!(json["schema_version"], 2);
+
+        let claude = &json["tools"][0];
+        assert_eq!(claude["identifier"], "claude");
+        assert_eq!(claude["installed_version"], "2.0.30");
+        assert_eq!(claude["latest_version"], "2.0.31");
+        assert_eq!(claude["install_method"], "bootstrap");
+        assert_eq!(claude["binary_path"], "/usr/local/bin/claude");
+        assert_eq!(claude["status"], "outdated");
+        assert_eq!(claude["origin"], "install script");
+        assert_eq!(json["tools"][1]["status"], "not_installed");
+        assert!(json["tools"][1]["installed_version"].is_null());
+    }
+
+    #[test]
+    fn it_writes_csv_with_a_header_row() {
+        let csv = render(Format::Csv);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some(
+                "name,identifier,installed_version,latest_version,install_method,binary_path,status,origin"
+            )
+        );
+        assert_eq!(
+            lines.next(),
+            Some(
+                "Claude Code,claude,2.0.30,2.0.31,bootstrap,/usr/local/bin/claude,outdated,install script"
+            )
+        );
+        assert_eq!(
+            lines.next(),
+            Some("Copilot CLI,copilot,,,npm,,not_installed,")
+        );
+    }
+
+    #[test]
+    fn it_writes_yaml_without_ansi_codes() {
+        let yaml = render(Format::Yaml);
+        assert!(!yaml.contains('\u{1b}'));
+        assert_eq!(
+            yaml,
+            r#"schema_version: 2
+tools:
+- name: Claude Code
+  identifier: claude
+  installed_version: 2.0.30
+  latest_version: 2.0.31
+  install_method: bootstrap
+  binary_path: /usr/local/bin/claude
+  status: outdated
+  origin: install script
+- name: Copilot CLI
+  identifier: copilot
+  installed_version: null
+  latest_version: null
+  install_method: npm
+  binary_path: null
+  status: not_installed
+  origin: null
+"#
+        );
+    }
+
+    #[test]
+    fn it_quotes_yaml_strings_that_would_read_as_other_values() {
+        let tricky = [
+            "yes",
+            "no",
+            "on",
+            "null",
+            "~",
+            "1.0",
+            "a: b",
+            "# c",
+            "-",
+            "line\nbreak",
+        ];
+        let tools: Vec<ToolVersion> = tricky
+            .iter()
+            .map(|name| ToolVersion::new(name).with_identifier(name))
+            .collect();
+        let mut out = Vec::new();
+        write(Format::Yaml, &tools, &mut out).unwrap();
+
+        let parsed: serde_json::Value = serde_yaml_ng::from_slice(&out).unwrap();
+        for (record, name) in parsed["tools"].as_array().unwrap().iter().zip(tricky) {
+            assert_eq!(record["name"], name);
+            assert_eq!(record["identifier"], name);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::PathBuf,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{paths, tools::Channel};
+
+const SETTINGS_FILE: &str = "config.toml";
+
+/// User preferences stored in `~/.config/ai-cli-apps/config.toml`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Settings {
+    /// Channel overrides keyed by tool identifier.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub channels: BTreeMap<String, Channel>,
+    /// Identifiers of tools that bulk upgrades leave alone.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub held: BTreeSet<String>,
+    /// How long looked-up latest versions are reused before they are checked again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl_minutes: Option<u64>,
+    /// Days uninstall archives are kept before they are purged; 0 keeps them forever.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive_retention_days: Option<u64>,
+    /// Refuse installer scripts whose content has not been approved, instead of asking.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub strict_scripts: bool,
+}
+
+impl Settings {
+    pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+    pub fn cache_ttl(&self) -> Duration {
+        self.cache_ttl_minutes
+            .map(|minutes| Duration::from_secs(minutes.saturating_mul(60)))
+            .unwrap_or(Self::DEFAULT_CACHE_TTL)
+    }
+
+    pub const DEFAULT_ARCHIVE_RETENTION_DAYS: u64 = 30;
+
+    /// How long uninstall archives are kept, or `None` to keep them forever.
+    pub fn archive_retention(&self) -> Option<Duration> {
+        match self
+            .archive_retention_days
+            .unwrap_or(Self::DEFAULT_ARCHIVE_RETENTION_DAYS)
+        {
+            0 => None,
+            days => Some(Duration::from_secs(days.saturating_mul(24 * 60 * 60))),
+        }
+    }
+
+    pub fn path() -> Result<PathBuf> {
+        Ok(paths::config_dir()?.join(SETTINGS_FILE))
+    }
+
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        match fs::read_to_string(&path) {
+            Ok(source) => toml::from_str(&source)
+                .with_context(|| format!("Failed to parse {}", path.display())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let source = toml::to_string_pretty(self).context("Failed to serialize settings")?;
+        fs::write(&path, source).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
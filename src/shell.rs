@@ -0,0 +1,281 @@
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+use crate::{cache, paths};
+
+const BLOCK_START: &str = "# >>> ai-cli-apps >>>";
+const BLOCK_END: &str = "# <<< ai-cli-apps <<<";
+
+/// Shells whose rc files ai-cli-apps edits to put `~/.local/bin` on PATH.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    pub const ALL: [Shell; 3] = [Shell::Bash, Shell::Zsh, Shell::Fish];
+
+    fn name(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+        }
+    }
+
+    /// The user's login shell from `$SHELL`.
+    fn current() -> Option<Self> {
+        let shell = PathBuf::from(env::var_os("SHELL")?);
+        let name = shell.file_name()?.to_str()?;
+        Self::ALL.into_iter().find(|s| s.name() == name)
+    }
+
+    fn rc_file(self, home: &Path) -> PathBuf {
+        match self {
+            Shell::Bash => home.join(".bashrc"),
+            Shell::Zsh => env::var_os("ZDOTDIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home.to_path_buf())
+                .join(".zshrc"),
+            Shell::Fish => match env::var_os("XDG_CONFIG_HOME") {
+                Some(config) if !config.is_empty() => PathBuf::from(config),
+                _ => home.join(".config"),
+            }
+            .join("fish")
+            .join("config.fish"),
+        }
+    }
+
+    /// The line prepending `~/.local/bin` to PATH in this shell's syntax.
+    fn path_line(self) -> &'static str {
+        match self {
+            Shell::Bash | Shell::Zsh => "export PATH=\"$HOME/.local/bin:$PATH\"",
+            Shell::Fish => "set -gx PATH \"$HOME/.local/bin\" $PATH",
+        }
+    }
+}
+
+/// An rc file that was changed, and the copy taken before the change.
+#[derive(Debug)]
+pub struct Edit {
+    pub path: PathBuf,
+    /// `None` when the file was created.
+    pub backup: Option<PathBuf>,
+}
+
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.backup {
+            Some(backup) => write!(f, "{} (backup: {})", self.path.display(), backup.display()),
+            None => write!(f, "{} (new file)", self.path.display()),
+        }
+    }
+}
+
+/// `~/.local/bin`, where bootstrap and Amp installers link their binaries.
+pub fn local_bin() -> Result<PathBuf> {
+    Ok(paths::home_dir()?.join(".local").join("bin"))
+}
+
+pub fn is_on_path(dir: &Path) -> bool {
+    env::var_os("PATH").is_some_and(|path| env::split_paths(&path).any(|entry| entry == dir))
+}
+
+/// Adds the managed PATH block to the rc file of every shell in use: the login shell and any
+/// other shell whose rc file exists.
+pub fn add_local_bin() -> Result<Vec<Edit>> {
+    let home = paths::home_dir()?;
+    let current = Shell::current();
+    let mut edits = Vec::new();
+    for shell in Shell::ALL {
+        let rc = shell.rc_file(&home);
+        if rc.exists() || current == Some(shell) {
+            let block = format!("{}\n{}\n{}\n", BLOCK_START, shell.path_line(), BLOCK_END);
+            edits.extend(edit(&rc, |contents| with_block(contents, &block))?);
+        }
+    }
+    Ok(edits)
+}
+
+/// Whether any rc file already has the managed PATH block.
+pub fn has_local_bin_block() -> bool {
+    let Ok(home) = paths::home_dir() else {
+        return false;
+    };
+    Shell::ALL.into_iter().any(|shell| {
+        fs::read_to_string(shell.rc_file(&home)).is_ok_and(|rc| rc.contains(BLOCK_START))
+    })
+}
+
+/// Removes the managed PATH block from every rc file.
+pub fn remove_local_bin() -> Result<Vec<Edit>> {
+    edit_existing(without_block)
+}
+
+/// Removes the `# <marker>` comment and PATH line an installer appended, e.g. Amp's `# amp`.
+pub fn remove_installer_lines(marker: &str) -> Result<Vec<Edit>> {
+    edit_existing(|contents| without_installer_lines(contents, marker))
+}
+
+fn edit_existing(change: impl Fn(&str) -> String) -> Result<Vec<Edit>> {
+    let home = paths::home_dir()?;
+    let mut edits = Vec::new();
+    for shell in Shell::ALL {
+        let rc = shell.rc_file(&home);
+        if rc.exists() {
+            edits.extend(edit(&rc, &change)?);
+        }
+    }
+    Ok(edits)
+}
+
+/// Rewrites `path` when `change` alters it, copying the original next to it first.
+fn edit(path: &Path, change: impl Fn(&str) -> String) -> Result<Option<Edit>> {
+    let existing = match fs::read_to_string(path) {
+        Ok(contents) => Some(contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let contents = existing.as_deref().unwrap_or_default();
+    let changed = change(contents);
+    if changed == contents {
+        return Ok(None);
+    }
+
+    // A file that did not exist yet has nothing to back up.
+    let backup = match existing {
+        Some(contents) => {
+            let mut backup = path.as_os_str().to_owned();
+            backup.push(format!(".ai-cli-apps-{}.bak", cache::now()));
+            let backup = PathBuf::from(backup);
+            fs::write(&backup, contents)
+                .with_context(|| format!("Failed to back up {}", path.display()))?;
+            Some(backup)
+        }
+        None => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            None
+        }
+    };
+    fs::write(path, changed).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(Some(Edit {
+        path: path.to_path_buf(),
+        backup,
+    }))
+}
+
+/// `contents` with `block` in place of the managed block, or appended when there is none.
+fn with_block(contents: &str, block: &str) -> String {
+    let stripped = without_block(contents);
+    let mut result = stripped.trim_end_matches('\n').to_string();
+    if !result.is_empty() {
+        result.push_str("\n\n");
+    }
+    result.push_str(block);
+    result
+}
+
+fn without_block(contents: &str) -> String {
+    let Some(start) = contents.find(BLOCK_START) else {
+        return contents.to_string();
+    };
+    let Some(end) = contents[start..]
+        .find(BLOCK_END)
+        .map(|i| start + i + BLOCK_END.len())
+    else {
+        return contents.to_string();
+    };
+    let before = contents[..start].trim_end_matches('\n');
+    let after = contents[end..].trim_start_matches('\n');
+    match (before.is_empty(), after.is_empty()) {
+        (true, _) => after.to_string(),
+        (false, true) => format!("{}\n", before),
+        (false, false) => format!("{}\n\n{}", before, after),
+    }
+}
+
+/// Drops a `# <marker>` comment only together with the installer's PATH line right after it,
+/// so a user's own lines that look alike stay.
+fn without_installer_lines(contents: &str, marker: &str) -> String {
+    let comment = format!("# {}", marker);
+    let is_installer_path = |line: &&str| is_installer_path_line(line, marker);
+    let mut lines = Vec::new();
+    let mut iter = contents.lines().peekable();
+    while let Some(line) = iter.next() {
+        if line.trim().eq_ignore_ascii_case(&comment) && iter.peek().is_some_and(is_installer_path)
+        {
+            iter.next();
+            continue;
+        }
+        lines.push(line);
+    }
+    let mut result = lines.join("\n");
+    if contents.ends_with('\n') && !result.is_empty() {
+        result.push('\n');
+    }
+    result
+}
+
+/// Whether `line` puts an installer's bin directory on PATH: `~/.local/bin`, where Amp's
+/// installer links its shim, or the installer's own `~/.<marker>/bin`, in sh
+/// (`export PATH="…:$PATH"`) or fish (`fish_add_path …`, `set -gx PATH … $PATH`) syntax.
+fn is_installer_path_line(line: &str, marker: &str) -> bool {
+    let line = line.trim();
+    let sets_path = [
+        "export PATH=",
+        "PATH=",
+        "fish_add_path ",
+        "set -gx PATH ",
+        "set -x PATH ",
+    ]
+    .iter()
+    .any(|prefix| line.starts_with(prefix));
+    let own_bin = format!("/.{}/bin", marker);
+    sets_path && (line.contains("/.local/bin") || line.contains(&own_bin))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BLOCK_END, BLOCK_START, Shell, with_block, without_block, without_installer_lines,
+    };
+
+    #[test]
+    fn it_adds_and_replaces_the_managed_block() {
+        let block = format!(
+            "{}\n{}\n{}\n",
+            BLOCK_START,
+            Shell::Bash.path_line(),
+            BLOCK_END
+        );
+        let once = with_block("alias ll='ls -l'\n", &block);
+        assert_eq!(once, format!("alias ll='ls -l'\n\n{}", block));
+        assert_eq!(with_block(&once, &block), once);
+        assert_eq!(without_block(&once), "alias ll='ls -l'\n");
+    }
+
+    #[test]
+    fn it_removes_lines_added_by_the_amp_installer() {
+        let rc = "export EDITOR=vim\n\n# amp\nexport PATH=\"$HOME/.local/bin:$PATH\"\n";
+        assert_eq!(without_installer_lines(rc, "amp"), "export EDITOR=vim\n\n");
+        let fish = "# amp\nfish_add_path $HOME/.local/bin\nset -gx EDITOR vim\n";
+        assert_eq!(without_installer_lines(fish, "amp"), "set -gx EDITOR vim\n");
+        let amp_home = "# amp\nexport PATH=\"/home/dev/.amp/bin:$PATH\"\n";
+        assert_eq!(without_installer_lines(amp_home, "amp"), "");
+        assert_eq!(without_installer_lines("# amplify\n", "amp"), "# amplify\n");
+
+        let own = "# amp\nexport PATH=\"$HOME/go/bin:$PATH\"\n";
+        assert_eq!(without_installer_lines(own, "amp"), own);
+        let alias = "# amp\nalias amp-path='echo $HOME/.local/bin'\n";
+        assert_eq!(without_installer_lines(alias, "amp"), alias);
+    }
+}
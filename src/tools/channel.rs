@@ -0,0 +1,49 @@
+use std::fmt;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Release channel a tool follows for latest-version checks, installs and upgrades.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    Stable,
+    #[default]
+    Latest,
+    Next,
+    Beta,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 4] = [
+        Channel::Stable,
+        Channel::Latest,
+        Channel::Next,
+        Channel::Beta,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Channel::Stable => "stable",
+            Channel::Latest => "latest",
+            Channel::Next => "next",
+            Channel::Beta => "beta",
+        }
+    }
+
+    /// npm dist-tags to try, in order.
+    pub fn npm_tags(self) -> &'static [&'static str] {
+        match self {
+            Channel::Stable => &["stable", "latest"],
+            Channel::Latest => &["latest"],
+            Channel::Next => &["next"],
+            Channel::Beta => &["beta"],
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,265 @@
+use std::{fs, io, path::Path};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use super::Tool;
+
+const BUILTIN_MANIFEST: &str = include_str!("manifest.toml");
+
+pub const USER_MANIFEST_FILE: &str = "tools.toml";
+
+#[derive(Deserialize)]
+struct Manifest {
+    #[serde(default, rename = "tool")]
+    tools: Vec<toml::Value>,
+}
+
+/// Parses a manifest, naming the offending `[[tool]]` entry when one is invalid.
+pub fn parse(source: &str) -> Result<Vec<Tool>> {
+    let manifest: Manifest = toml::from_str(source).context("Invalid tool manifest")?;
+
+    let mut tools: Vec<Tool> = Vec::with_capacity(manifest.tools.len());
+    for (index, entry) in manifest.tools.into_iter().enumerate() {
+        let label = entry
+            .get("name")
+            .and_then(|name| name.as_str())
+            .map(|name| format!("tool #{} ('{}')", index + 1, name))
+            .unwrap_or_else(|| format!("tool #{}", index + 1));
+
+        let tool: Tool = entry
+            .try_into()
+            .with_context(|| format!("Invalid {}", label))?;
+        validate(&tool).with_context(|| format!("Invalid {}", label))?;
+
+        if tools.iter().any(|t| t.identifier == tool.identifier) {
+            anyhow::bail!(
+                "Invalid {}: identifier '{}' is already used",
+                label,
+                tool.identifier
+            );
+        }
+        tools.push(tool);
+    }
+
+    Ok(tools)
+}
+
+fn validate(tool: &Tool) -> Result<()> {
+    if tool.name.trim().is_empty() {
+        anyhow::bail!("`name` must not be empty");
+    }
+    if tool.identifier.trim().is_empty() {
+        anyhow::bail!("`identifier` must not be empty");
+    }
+    if tool.install_methods.is_empty() {
+        anyhow::bail!("`install` must list at least one install method");
+    }
+    if tool.check_command.is_empty() {
+        anyhow::bail!("`check` must name the version command, e.g. [\"tool\", \"--version\"]");
+    }
+    // Any listed method may be the one that installs the tool, so each must follow the channel.
+    if let Some(method) = tool
+        .install_methods
+        .iter()
+        .find(|method| !tool.channels_for(method).contains(&tool.channel))
+    {
+        anyhow::bail!(
+            "channel '{}' is not supported by its {} install method",
+            tool.channel,
+            method.kind()
+        );
+    }
+    Ok(())
+}
+
+pub fn builtin() -> Vec<Tool> {
+    parse(BUILTIN_MANIFEST).expect("built-in tool manifest is valid")
+}
+
+/// Loads user-defined tools, returning nothing when the file does not exist.
+pub fn user(path: &Path) -> Result<Vec<Tool>> {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+        }
+    };
+
+    parse(&source).with_context(|| format!("Failed to load {}", path.display()))
+}
+
+/// Adds user tools to the catalog; a user tool replaces a built-in one with the same identifier.
+pub fn merge(mut tools: Vec<Tool>, user_tools: Vec<Tool>) -> Vec<Tool> {
+    for tool in user_tools {
+        match tools.iter_mut().find(|t| t.identifier == tool.identifier) {
+            Some(existing) => *existing = tool,
+            None => tools.push(tool),
+        }
+    }
+    tools
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{builtin, merge, parse};
+    use crate::tools::{InstallMethod, LatestSource};
+
+    #[test]
+    fn it_parses_the_builtin_manifest() {
+        let tools = builtin();
+        assert_eq!(tools.len(), 10);
+
+        let opencode = tools.iter().find(|t| t.identifier == "opencode").unwrap();
+        assert!(matches!(
+            opencode.install_methods.as_slice(),
+            [
+                InstallMethod::Brew(_),
+                InstallMethod::Npm(_),
+                InstallMethod::Bootstrap(_)
+            ]
+        ));
+        assert_eq!(opencode.config_dirs, vec![".opencode"]);
+        assert_eq!(opencode.extra_binary_paths, vec![".opencode/bin/opencode"]);
+    }
+
+    #[test]
+    fn it_declares_a_latest_source_for_every_builtin_tool() {
+        for tool in builtin() {
+            for method in &tool.install_methods {
+                assert!(
+                    tool.latest_source_with(method, true).is_some(),
+                    "{} has no latest-version source for {:?}",
+                    tool.name,
+                    method
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_resolves_claude_from_its_bootstrap_distribution() {
+        let claude = builtin()
+            .into_iter()
+            .find(|t| t.identifier == "claude")
+            .unwrap();
+        let [InstallMethod::Bootstrap(bootstrap)] = claude.install_methods.as_slice() else {
+            panic!("Claude Code should install via bootstrap");
+        };
+        let Some(LatestSource::FirstOf(sources)) = &claude.latest else {
+            panic!("Claude Code should try several latest sources");
+        };
+
+        assert!(matches!(
+            sources.as_slice(),
+            [LatestSource::ReleaseBucket(bucket), LatestSource::Npm(package)]
+                if bootstrap.starts_with(bucket.as_str()) && package == "@anthropic-ai/claude-code"
+        ));
+    }
+
+    #[test]
+    fn it_rejects_entries_without_install_method() {
+        let source = r#"
+            [[tool]]
+            name = "Broken"
+            identifier = "broken"
+            check = ["broken", "--version"]
+        "#;
+        let err = format!("{:#}", parse(source).unwrap_err());
+        assert!(err.contains("tool #1 ('Broken')"), "{}", err);
+        assert!(err.contains("install"), "{}", err);
+    }
+
+    #[test]
+    fn it_rejects_channels_the_install_method_cannot_follow() {
+        let source = r#"
+            [[tool]]
+            name = "Scripted"
+            identifier = "scripted"
+            install = { bootstrap = "https://example.com/install.sh" }
+            check = ["scripted", "--version"]
+            channel = "beta"
+        "#;
+        let err = format!("{:#}", parse(source).unwrap_err());
+        assert!(err.contains("channel 'beta'"), "{}", err);
+    }
+
+    #[test]
+    fn it_checks_the_channel_against_every_fallback() {
+        let tools = parse(
+            r#"
+            [[tool]]
+            name = "Nightly"
+            identifier = "nightly"
+            install = [{ brew = "nightly" }, { npm = "nightly" }]
+            check = ["nightly", "--version"]
+            channel = "next"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(tools[0].install_methods.len(), 2);
+
+        let source = r#"
+            [[tool]]
+            name = "Beta"
+            identifier = "beta"
+            install = [{ npm = "beta" }, { bootstrap = "https://example.com/install.sh" }]
+            check = ["beta", "--version"]
+            channel = "beta"
+        "#;
+        let err = format!("{:#}", parse(source).unwrap_err());
+        assert!(
+            err.contains("channel 'beta' is not supported by its bootstrap install method"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn it_names_the_binary_after_the_check_command() {
+        let tools = parse(
+            r#"
+            [[tool]]
+            name = "Acme Agent"
+            identifier = "acme"
+            install = { bootstrap = "https://example.com/install.sh" }
+            check = ["acme-agent", "--version"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(tools[0].binary_name(), "acme-agent");
+        assert!(builtin().iter().all(|t| t.binary_name() == t.identifier));
+    }
+
+    #[test]
+    fn it_merges_user_tools_over_builtins() {
+        let user_tools = parse(
+            r#"
+            [[tool]]
+            name = "Internal Agent"
+            identifier = "iagent"
+            install = { npm = "@acme/iagent" }
+            check = ["iagent", "--version"]
+            latest = { npm = "@acme/iagent" }
+
+            [[tool]]
+            name = "Codex CLI"
+            identifier = "codex"
+            install = { npm = "@openai/codex" }
+            check = ["codex", "--version"]
+            "#,
+        )
+        .unwrap();
+
+        let tools = merge(builtin(), user_tools);
+        assert_eq!(tools.len(), 11);
+
+        let codex = tools.iter().find(|t| t.identifier == "codex").unwrap();
+        assert!(matches!(
+            codex.install_methods.as_slice(),
+            [InstallMethod::Npm(p)] if p == "@openai/codex"
+        ));
+        assert!(tools.iter().any(|t| t.identifier == "iagent"));
+    }
+}
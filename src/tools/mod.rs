@@ -1,95 +1,467 @@
-mod amp;
-mod claude;
-mod cline;
-mod codex;
-mod copilot;
-mod cursor_agent;
-mod factory;
-mod gemini;
-mod kilo;
-mod opencode;
-
-use std::process::Command;
-
-pub use amp::{definition as amp_tool, installed_version as amp_installed_version};
-pub use claude::{definition as claude_tool, installed_version as claude_installed_version};
-pub use cline::{definition as cline_tool, installed_version as cline_installed_version};
-pub use codex::{definition as codex_tool, installed_version as codex_installed_version};
-pub use copilot::{definition as copilot_tool, installed_version as copilot_installed_version};
-pub use cursor_agent::{
-    definition as cursor_agent_tool, installed_version as cursor_agent_installed_version,
-};
-pub use factory::{
-    definition as factory_cli_tool, installed_version as factory_cli_installed_version,
+mod channel;
+mod manifest;
+mod origin;
+mod version_parser;
+
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
 };
-pub use gemini::{definition as gemini_tool, installed_version as gemini_installed_version};
-pub use kilo::{definition as kilo_tool, installed_version as kilo_installed_version};
-pub use opencode::{definition as opencode_tool, installed_version as opencode_installed_version};
 
-#[derive(Debug, Clone)]
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::{paths, settings::Settings, version::Version};
+
+pub use channel::Channel;
+pub use origin::Origin;
+pub use version_parser::{Component, VersionParser};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum InstallMethod {
     Npm(String),
     Bootstrap(String),
     Amp(String),
     Brew(String),
+    BrewCask(String),
 }
 
-#[derive(Debug, Clone)]
+impl InstallMethod {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            InstallMethod::Npm(_) => "npm",
+            InstallMethod::Bootstrap(_) => "bootstrap",
+            InstallMethod::Amp(_) => "amp",
+            InstallMethod::Brew(_) => "brew",
+            InstallMethod::BrewCask(_) => "brew_cask",
+        }
+    }
+
+    /// The npm package, Homebrew formula or cask, or installer script URL.
+    pub fn source(&self) -> &str {
+        match self {
+            InstallMethod::Npm(source)
+            | InstallMethod::Bootstrap(source)
+            | InstallMethod::Amp(source)
+            | InstallMethod::Brew(source)
+            | InstallMethod::BrewCask(source) => source,
+        }
+    }
+
+    /// The program this method runs.
+    pub fn program(&self) -> &'static str {
+        match self {
+            InstallMethod::Npm(_) => "npm",
+            InstallMethod::Brew(_) | InstallMethod::BrewCask(_) => "brew",
+            InstallMethod::Bootstrap(_) | InstallMethod::Amp(_) => "bash",
+        }
+    }
+
+    /// Whether the program this method runs is on PATH.
+    pub fn is_available(&self) -> bool {
+        find_on_path(self.program()).is_some()
+    }
+}
+
+/// Accepts either a single install method or an ordered list of them.
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<InstallMethod>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(InstallMethod),
+        Many(Vec<InstallMethod>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(method) => vec![method],
+        OneOrMany::Many(methods) => methods,
+    })
+}
+
+/// Where the latest released version of a tool is looked up.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatestSource {
+    /// `dist-tags.latest` of an npm package.
+    Npm(String),
+    /// Stable version of a Homebrew formula.
+    #[serde(alias = "brew")]
+    BrewFormula(String),
+    /// Version of a Homebrew cask.
+    BrewCask(String),
+    /// Tag of the latest GitHub release of `owner/repo`.
+    #[serde(rename = "github_release", alias = "github")]
+    GitHubRelease(String),
+    /// Value of a `NAME=` assignment in a shell script, e.g. Factory's `VER=`.
+    ScriptVariable { url: String, variable: String },
+    /// First capture group of `regex` (or the whole match) in the body of `url`.
+    Url { url: String, regex: String },
+    /// Build referenced by the download URLs in Cursor's `cursor.com/install` script.
+    CursorInstallScript(String),
+    /// Release bucket serving plain-text `stable` and `latest` version pointers.
+    ReleaseBucket(String),
+    /// The first of several sources that yields a version.
+    FirstOf(Vec<LatestSource>),
+}
+
+/// Compact form naming the kind of source and where it looks, e.g. `npm:@github/copilot`.
+impl fmt::Display for LatestSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LatestSource::Npm(package) => write!(f, "npm:{}", package),
+            LatestSource::BrewFormula(formula) => write!(f, "brew:{}", formula),
+            LatestSource::BrewCask(cask) => write!(f, "cask:{}", cask),
+            LatestSource::GitHubRelease(repo) => write!(f, "github:{}", repo),
+            LatestSource::ScriptVariable { url, variable } => {
+                write!(f, "script:{}#{}", url, variable)
+            }
+            LatestSource::Url { url, regex } => write!(f, "url:{}#{}", url, regex),
+            LatestSource::CursorInstallScript(url) => write!(f, "cursor:{}", url),
+            LatestSource::ReleaseBucket(url) => write!(f, "bucket:{}", url),
+            LatestSource::FirstOf(sources) => {
+                let sources: Vec<String> = sources.iter().map(ToString::to_string).collect();
+                write!(f, "first_of({})", sources.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct Tool {
     pub name: String,
-    pub install_method: InstallMethod,
+    pub identifier: String,
+    /// Install methods in order of preference.
+    #[serde(rename = "install", deserialize_with = "one_or_many")]
+    pub install_methods: Vec<InstallMethod>,
+    #[serde(rename = "check")]
     pub check_command: Vec<String>,
-    pub binary_name: Option<String>,
+    #[serde(rename = "upgrade", default)]
+    pub upgrade_command: Vec<String>,
+    #[serde(rename = "version", default)]
+    pub version_parser: VersionParser,
+    #[serde(default)]
+    pub latest: Option<LatestSource>,
+    /// Channel followed unless overridden in the user's settings.
+    #[serde(default)]
+    pub channel: Channel,
+    /// Whether the bootstrap script takes a `stable`/`latest`/version argument.
+    #[serde(default)]
+    pub accepts_target: bool,
+    #[serde(default)]
     pub config_dirs: Vec<String>,
+    #[serde(default)]
     pub extra_binary_paths: Vec<String>,
 }
 
 impl Tool {
-    pub fn new(name: &str, install_method: InstallMethod, check_command: Vec<String>) -> Self {
-        Self {
-            name: name.to_string(),
-            install_method,
-            check_command,
-            binary_name: None,
-            config_dirs: Vec::new(),
-            extra_binary_paths: Vec::new(),
+    /// Name of the tool's executable, taken from the check command, which need not match the
+    /// identifier of a user-defined tool.
+    pub fn binary_name(&self) -> &str {
+        self.check_command
+            .first()
+            .and_then(|program| Path::new(program).file_name())
+            .and_then(|name| name.to_str())
+            .unwrap_or(&self.identifier)
+    }
+
+    /// The first install method whose package manager is available, else the preferred one.
+    pub fn install_method(&self) -> &InstallMethod {
+        self.install_methods
+            .iter()
+            .find(|method| method.is_available())
+            .unwrap_or(&self.install_methods[0])
+    }
+
+    /// The method managing the installed binary, else the one a fresh install would use.
+    ///
+    /// A tool the catalog installs with Homebrew may have been installed with npm instead;
+    /// upgrading or removing it must go through npm.
+    pub fn installed_method(&self) -> InstallMethod {
+        self.origin()
+            .and_then(|origin| origin.install_method(&self.install_methods))
+            .unwrap_or_else(|| self.install_method().clone())
+    }
+
+    /// Where the binary that would run for this tool was installed from.
+    pub fn origin(&self) -> Option<Origin> {
+        let binary = self.binary_paths().into_iter().next()?;
+        Origin::detect(&binary, &paths::home_dir().ok()?, &self.extra_binary_paths)
+    }
+
+    /// Every distinct binary for this tool, in the order the shell would try them, followed by
+    /// copies at the manifest's extra binary paths. Symlinks to the same file count once.
+    pub fn binary_paths(&self) -> Vec<PathBuf> {
+        let Some(program) = self.check_command.first() else {
+            return Vec::new();
+        };
+        let extra: Vec<PathBuf> = match paths::home_dir() {
+            Ok(home) => self
+                .extra_binary_paths
+                .iter()
+                .map(|extra| home.join(extra))
+                .filter(|path| is_executable(path))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+
+        distinct_binaries(find_all_on_path(program).into_iter().chain(extra))
+    }
+
+    /// Version and origin of every binary from [`Tool::binary_paths`].
+    pub fn installations(&self) -> Vec<Installation> {
+        self.binary_paths()
+            .into_iter()
+            .map(|path| self.inspect(path))
+            .collect()
+    }
+
+    fn inspect(&self, path: PathBuf) -> Installation {
+        let args: Vec<&str> = self.check_command[1..].iter().map(String::as_str).collect();
+        let version = path
+            .to_str()
+            .and_then(|binary| command_output(binary, &args))
+            .map(|output| self.version_text(&output).0);
+        let origin = paths::home_dir()
+            .ok()
+            .and_then(|home| Origin::detect(&path, &home, &self.extra_binary_paths));
+        Installation {
+            path,
+            version,
+            origin,
         }
     }
 
-    pub fn with_binary_name(mut self, binary_name: &str) -> Self {
-        self.binary_name = Some(binary_name.to_string());
-        self
+    /// Version text and components picked out of the check command's output.
+    fn version_text(&self, output: &str) -> (String, Vec<Component>) {
+        match self.version_parser.parse(output) {
+            Some(parsed) => (parsed.version, parsed.components),
+            None => (
+                output.lines().next().unwrap_or_default().to_string(),
+                Vec::new(),
+            ),
+        }
     }
 
-    pub fn with_config_dir(mut self, config_dir: &str) -> Self {
-        self.config_dirs.push(config_dir.to_string());
-        self
+    /// The declared latest source, or the registry behind `method`. Without Homebrew, npm
+    /// packages among the fallbacks can still be looked up over HTTP.
+    pub fn latest_source_for(&self, method: &InstallMethod) -> Option<LatestSource> {
+        self.latest_source_with(method, find_on_path("brew").is_some())
     }
 
-    pub fn with_extra_binary_path(mut self, path: &str) -> Self {
-        self.extra_binary_paths.push(path.to_string());
-        self
+    fn latest_source_with(&self, method: &InstallMethod, has_brew: bool) -> Option<LatestSource> {
+        if let Some(latest) = &self.latest {
+            return Some(latest.clone());
+        }
+        match method {
+            InstallMethod::Npm(package) => Some(LatestSource::Npm(package.clone())),
+            InstallMethod::Brew(formula) if has_brew => {
+                Some(LatestSource::BrewFormula(formula.clone()))
+            }
+            InstallMethod::BrewCask(cask) if has_brew => Some(LatestSource::BrewCask(cask.clone())),
+            _ => self.install_methods.iter().find_map(|method| match method {
+                InstallMethod::Npm(package) => Some(LatestSource::Npm(package.clone())),
+                _ => None,
+            }),
+        }
+    }
+
+    pub fn supported_channels(&self) -> Vec<Channel> {
+        self.channels_for(self.install_method())
+    }
+
+    pub fn channels_for(&self, method: &InstallMethod) -> Vec<Channel> {
+        if self.upgrade_command_for(method).is_some() {
+            return vec![Channel::Latest];
+        }
+        match method {
+            InstallMethod::Npm(_) => Channel::ALL.to_vec(),
+            // Homebrew's `--HEAD` builds stand in for the next channel.
+            InstallMethod::Brew(_) => vec![Channel::Stable, Channel::Latest, Channel::Next],
+            InstallMethod::Bootstrap(_) if self.accepts_target => {
+                vec![Channel::Stable, Channel::Latest]
+            }
+            InstallMethod::Bootstrap(_) | InstallMethod::Amp(_) | InstallMethod::BrewCask(_) => {
+                vec![Channel::Latest]
+            }
+        }
+    }
+
+    /// The tool's own upgrade command, which only a copy installed by its script can run; a
+    /// copy from npm or Homebrew is upgraded through that package manager instead.
+    pub fn upgrade_command_for(&self, method: &InstallMethod) -> Option<&[String]> {
+        let scripted = matches!(method, InstallMethod::Amp(_) | InstallMethod::Bootstrap(_));
+        (scripted && !self.upgrade_command.is_empty()).then_some(self.upgrade_command.as_slice())
+    }
+
+    /// Whether `method` can install an exact version.
+    pub fn can_pin(&self, method: &InstallMethod) -> bool {
+        match method {
+            InstallMethod::Npm(_) | InstallMethod::Brew(_) => true,
+            InstallMethod::Bootstrap(_) => self.accepts_target,
+            InstallMethod::Amp(_) | InstallMethod::BrewCask(_) => false,
+        }
     }
 
     pub fn is_installed(&self) -> bool {
-        if self.check_command.is_empty() {
-            return false;
+        self.run_check().is_some()
+    }
+
+    pub fn installed_version(&self) -> ToolVersion {
+        let home = paths::home_dir().ok();
+        let installed = self.run_check().map(|(output, binary_path, detection)| {
+            let (version, components) = self.version_text(&output);
+            let origin = binary_path
+                .as_deref()
+                .zip(home.as_deref())
+                .and_then(|(binary, home)| Origin::detect(binary, home, &self.extra_binary_paths));
+            InstalledVersion {
+                parsed: Version::parse(&version),
+                version,
+                components,
+                raw_output: output,
+                binary_path,
+                detection,
+                origin,
+            }
+        });
+
+        let method = installed
+            .as_ref()
+            .and_then(|installed| installed.origin.as_ref())
+            .and_then(|origin| origin.install_method(&self.install_methods))
+            .unwrap_or_else(|| self.install_method().clone());
+
+        // Only run every copy's version command when there is more than one.
+        let copies = match &installed {
+            Some(_) => {
+                let paths = self.binary_paths();
+                if paths.len() > 1 {
+                    paths.into_iter().map(|path| self.inspect(path)).collect()
+                } else {
+                    Vec::new()
+                }
+            }
+            None => Vec::new(),
+        };
+
+        ToolVersion::new(&self.name)
+            .with_installed(installed)
+            .with_copies(copies)
+            .with_identifier(&self.identifier)
+            .with_latest_source(self.latest_source_for(&method))
+            .with_channel(self.channel)
+            .with_install_method(method)
+    }
+
+    /// Runs the check command from PATH, then from the manifest's extra binary paths.
+    fn run_check(&self) -> Option<(String, Option<PathBuf>, Detection)> {
+        let (program, args) = self.check_command.split_first()?;
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+        if let Some(output) = command_output(program, &args) {
+            return Some((output, find_on_path(program), Detection::Path));
         }
 
-        Command::new(&self.check_command[0])
-            .args(&self.check_command[1..])
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false)
+        // Installers such as OpenCode's put the binary in a directory that may not be on PATH.
+        let home = paths::home_dir().ok()?;
+        self.extra_binary_paths
+            .iter()
+            .map(|extra| home.join(extra))
+            .filter(|path| path.is_file())
+            .find_map(|path| {
+                let output = command_output(path.to_str()?, &args)?;
+                Some((output, Some(path), Detection::KnownLocation))
+            })
     }
 }
 
+/// How an installed binary was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Detection {
+    /// The check command succeeded from PATH.
+    Path,
+    /// The binary was found at one of the tool's `extra_binary_paths`.
+    KnownLocation,
+}
+
+#[derive(Debug, Clone)]
+pub struct InstalledVersion {
+    /// Version text picked out by the tool's version parser.
+    pub version: String,
+    pub parsed: Option<Version>,
+    pub components: Vec<Component>,
+    pub raw_output: String,
+    pub binary_path: Option<PathBuf>,
+    pub detection: Detection,
+    pub origin: Option<Origin>,
+}
+
+impl fmt::Display for InstalledVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.version)?;
+        if !self.components.is_empty() {
+            let components: Vec<String> = self
+                .components
+                .iter()
+                .map(|c| format!("{}: {}", c.name, c.version))
+                .collect();
+            write!(f, " ({})", components.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// One binary for a tool, as found by [`Tool::installations`].
+#[derive(Debug, Clone)]
+pub struct Installation {
+    pub path: PathBuf,
+    pub version: Option<String>,
+    pub origin: Option<Origin>,
+}
+
+impl Installation {
+    /// The install method that can remove this copy.
+    pub fn install_method(&self, tool: &Tool) -> Option<InstallMethod> {
+        self.origin
+            .as_ref()
+            .and_then(|origin| origin.install_method(&tool.install_methods))
+    }
+}
+
+/// `paths` without the ones that resolve to a binary already listed, e.g. a symlink in
+/// `~/.local/bin` to the same file as a PATH entry.
+fn distinct_binaries(paths: impl IntoIterator<Item = PathBuf>) -> Vec<PathBuf> {
+    let mut seen = Vec::new();
+    paths
+        .into_iter()
+        .filter(|path| {
+            let real = fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+            let first = !seen.contains(&real);
+            seen.push(real);
+            first
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone)]
 pub struct ToolVersion {
     pub name: String,
-    pub installed: Option<String>,
+    pub installed: Option<InstalledVersion>,
     pub latest: Option<String>,
     pub identifier: Option<String>,
+    pub latest_source: Option<LatestSource>,
+    pub channel: Channel,
+    pub install_method: Option<InstallMethod>,
+    /// Age of a cached latest version that could not be refreshed.
+    pub latest_age: Option<Duration>,
+    /// Every installed copy when there is more than one; the first is the one that runs.
+    pub copies: Vec<Installation>,
 }
 
 impl ToolVersion {
@@ -99,48 +471,63 @@ impl ToolVersion {
             installed: None,
             latest: None,
             identifier: None,
+            latest_source: None,
+            channel: Channel::default(),
+            install_method: None,
+            latest_age: None,
+            copies: Vec::new(),
         }
     }
 
-    pub fn with_installed(mut self, version: Option<String>) -> Self {
+    pub fn with_installed(mut self, version: Option<InstalledVersion>) -> Self {
         self.installed = version;
         self
     }
 
+    pub fn with_copies(mut self, copies: Vec<Installation>) -> Self {
+        self.copies = copies;
+        self
+    }
+
     pub fn with_identifier(mut self, identifier: &str) -> Self {
         self.identifier = Some(identifier.to_string());
         self
     }
+
+    pub fn with_latest_source(mut self, source: Option<LatestSource>) -> Self {
+        self.latest_source = source;
+        self
+    }
+
+    pub fn with_channel(mut self, channel: Channel) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    pub fn with_install_method(mut self, method: InstallMethod) -> Self {
+        self.install_method = Some(method);
+        self
+    }
 }
 
-pub fn catalog() -> Vec<Tool> {
-    vec![
-        claude_tool(),
-        amp_tool(),
-        codex_tool(),
-        cursor_agent_tool(),
-        copilot_tool(),
-        kilo_tool(),
-        gemini_tool(),
-        cline_tool(),
-        opencode_tool(),
-        factory_cli_tool(),
-    ]
-}
-
-pub fn installed_versions() -> Vec<ToolVersion> {
-    vec![
-        claude_installed_version(),
-        amp_installed_version(),
-        codex_installed_version(),
-        cursor_agent_installed_version(),
-        copilot_installed_version(),
-        kilo_installed_version(),
-        gemini_installed_version(),
-        cline_installed_version(),
-        opencode_installed_version(),
-        factory_cli_installed_version(),
-    ]
+/// Built-in tools merged with the user's `~/.config/ai-cli-apps/tools.toml`.
+///
+/// Channels chosen with `ai-cli-apps channel` replace each tool's default channel.
+pub fn catalog() -> Result<Vec<Tool>> {
+    let user_tools = manifest::user(&paths::config_dir()?.join(manifest::USER_MANIFEST_FILE))?;
+    let settings = Settings::load()?;
+
+    let mut tools = manifest::merge(manifest::builtin(), user_tools);
+    for tool in &mut tools {
+        if let Some(channel) = settings.channels.get(&tool.identifier) {
+            tool.channel = *channel;
+        }
+    }
+    Ok(tools)
+}
+
+pub fn installed_versions() -> Result<Vec<ToolVersion>> {
+    Ok(catalog()?.iter().map(Tool::installed_version).collect())
 }
 
 pub(crate) fn command_output(cmd: &str, args: &[&str]) -> Option<String> {
@@ -158,3 +545,71 @@ pub(crate) fn command_output(cmd: &str, args: &[&str]) -> Option<String> {
             }
         })
 }
+
+/// Every executable named `binary` in PATH, in PATH order.
+pub(crate) fn find_all_on_path(binary: &str) -> Vec<PathBuf> {
+    if binary.contains(std::path::MAIN_SEPARATOR) {
+        return find_on_path(binary).into_iter().collect();
+    }
+
+    let Some(path) = env::var_os("PATH") else {
+        return Vec::new();
+    };
+    env::split_paths(&path)
+        .map(|dir| dir.join(binary))
+        .filter(|candidate| is_executable(candidate))
+        .collect()
+}
+
+/// First executable named `binary` in PATH.
+pub(crate) fn find_on_path(binary: &str) -> Option<PathBuf> {
+    if binary.contains(std::path::MAIN_SEPARATOR) {
+        let path = PathBuf::from(binary);
+        return is_executable(&path).then_some(path);
+    }
+
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path)
+        .map(|dir| dir.join(binary))
+        .find(|candidate| is_executable(candidate))
+}
+
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::distinct_binaries;
+    use std::fs;
+
+    #[cfg(unix)]
+    #[test]
+    fn it_lists_a_binary_reached_through_a_symlink_once() {
+        let temp = tempfile::tempdir().unwrap();
+        let real = temp.path().join("lib/codex");
+        let link = temp.path().join("bin/codex");
+        let other = temp.path().join("npm/codex");
+        for path in [&real, &link, &other] {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+        }
+        fs::write(&real, "").unwrap();
+        fs::write(&other, "").unwrap();
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        assert_eq!(
+            distinct_binaries([link.clone(), real.clone(), other.clone(), link.clone()]),
+            vec![link, other]
+        );
+    }
+}
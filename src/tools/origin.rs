@@ -0,0 +1,176 @@
+use std::{
+    fmt, fs,
+    path::{Component, Path},
+};
+
+use super::InstallMethod;
+
+/// How an installed binary actually got onto the system, worked out from where it lives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    /// Global npm package, e.g. `<prefix>/lib/node_modules/@google/gemini-cli`.
+    Npm(String),
+    /// Homebrew formula in the Cellar.
+    Brew(String),
+    /// Homebrew cask in the Caskroom.
+    BrewCask(String),
+    /// Amp's installer, which keeps everything in `~/.amp`.
+    Amp,
+    /// An installer script's symlink in `~/.local/bin` or a tool's own directory such as
+    /// `~/.opencode/bin`.
+    Script,
+}
+
+impl Origin {
+    /// Works out the origin of `binary`, following symlinks to the file they point at.
+    pub fn detect(binary: &Path, home: &Path, extra_binary_paths: &[String]) -> Option<Self> {
+        let resolved = fs::canonicalize(binary).unwrap_or_else(|_| binary.to_path_buf());
+        let home = fs::canonicalize(home).unwrap_or_else(|_| home.to_path_buf());
+
+        // Homebrew node formulae keep an npm tree under `Cellar/<formula>/<v>/libexec`, so the
+        // Cellar and Caskroom win over `node_modules`.
+        if let Some(formula) = component_after(&resolved, "Cellar") {
+            return Some(Origin::Brew(formula));
+        }
+        if let Some(cask) = component_after(&resolved, "Caskroom") {
+            return Some(Origin::BrewCask(cask));
+        }
+        if let Some(package) = npm_package(&resolved) {
+            return Some(Origin::Npm(package));
+        }
+        if resolved.starts_with(home.join(".amp")) {
+            return Some(Origin::Amp);
+        }
+
+        let local = home.join(".local");
+        let local_bin = local.join("bin");
+        let in_local_bin = [binary, resolved.as_path()]
+            .iter()
+            .any(|path| path.parent() == Some(local_bin.as_path()));
+        let in_tool_dir = extra_binary_paths.iter().any(|extra| {
+            let extra = home.join(extra);
+            extra.parent().is_some_and(|dir| resolved.starts_with(dir))
+        });
+        if in_local_bin || in_tool_dir || resolved.starts_with(local.join("share")) {
+            return Some(Origin::Script);
+        }
+        None
+    }
+
+    /// The install method that manages this origin. Installer origins use the tool's own
+    /// installer; package origins carry their package or formula name.
+    pub fn install_method(&self, declared: &[InstallMethod]) -> Option<InstallMethod> {
+        match self {
+            Origin::Npm(package) => Some(InstallMethod::Npm(package.clone())),
+            Origin::Brew(formula) => Some(InstallMethod::Brew(formula.clone())),
+            Origin::BrewCask(cask) => Some(InstallMethod::BrewCask(cask.clone())),
+            Origin::Amp => declared
+                .iter()
+                .find(|method| matches!(method, InstallMethod::Amp(_)))
+                .cloned(),
+            Origin::Script => declared
+                .iter()
+                .find(|method| matches!(method, InstallMethod::Bootstrap(_)))
+                .cloned(),
+        }
+    }
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Npm(package) => write!(f, "npm {}", package),
+            Origin::Brew(formula) => write!(f, "brew {}", formula),
+            Origin::BrewCask(cask) => write!(f, "brew cask {}", cask),
+            Origin::Amp => write!(f, "amp installer"),
+            Origin::Script => write!(f, "install script"),
+        }
+    }
+}
+
+/// Package name following the first `node_modules` directory, including its `@scope`.
+fn npm_package(path: &Path) -> Option<String> {
+    let mut components = components_after(path, "node_modules")?;
+    let name = components.next()?;
+    if name.starts_with('@') {
+        Some(format!("{}/{}", name, components.next()?))
+    } else {
+        Some(name)
+    }
+}
+
+fn component_after(path: &Path, marker: &str) -> Option<String> {
+    components_after(path, marker)?.next()
+}
+
+fn components_after<'a>(path: &'a Path, marker: &str) -> Option<impl Iterator<Item = String> + 'a> {
+    let mut components = path.components();
+    components.by_ref().find(|c| c.as_os_str() == marker)?;
+    Some(components.filter_map(|c| match c {
+        Component::Normal(name) => name.to_str().map(str::to_string),
+        _ => None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Origin;
+    use std::{fs, path::Path};
+
+    #[test]
+    fn it_reads_package_names_from_install_paths() {
+        let home = Path::new("/home/dev");
+        let cases = [
+            (
+                "/usr/local/lib/node_modules/@google/gemini-cli/dist/index.js",
+                Origin::Npm("@google/gemini-cli".to_string()),
+            ),
+            (
+                "/home/dev/.nvm/versions/node/v22.0.0/lib/node_modules/opencode-ai/bin/opencode",
+                Origin::Npm("opencode-ai".to_string()),
+            ),
+            (
+                "/opt/homebrew/Cellar/gemini-cli/0.9.0/libexec/lib/node_modules/@google/gemini-cli/dist/index.js",
+                Origin::Brew("gemini-cli".to_string()),
+            ),
+            (
+                "/opt/homebrew/Caskroom/codex/0.46.0/codex-aarch64-apple-darwin",
+                Origin::BrewCask("codex".to_string()),
+            ),
+            ("/home/dev/.amp/bin/amp", Origin::Amp),
+            ("/home/dev/.local/bin/claude", Origin::Script),
+            ("/home/dev/.opencode/bin/opencode", Origin::Script),
+        ];
+
+        let extra = [".opencode/bin/opencode".to_string()];
+        for (path, expected) in cases {
+            assert_eq!(
+                Origin::detect(Path::new(path), home, &extra),
+                Some(expected),
+                "{}",
+                path
+            );
+        }
+        assert_eq!(
+            Origin::detect(Path::new("/usr/bin/gemini"), home, &extra),
+            None
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_follows_symlinks_to_the_real_install() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let package = root.join("lib/node_modules/@openai/codex/bin");
+        let bin = root.join("bin");
+        fs::create_dir_all(&package).unwrap();
+        fs::create_dir_all(&bin).unwrap();
+        fs::write(package.join("codex.js"), "").unwrap();
+        std::os::unix::fs::symlink(package.join("codex.js"), bin.join("codex")).unwrap();
+
+        let origin = Origin::detect(&bin.join("codex"), Path::new("/home/dev"), &[]);
+
+        assert_eq!(origin, Some(Origin::Npm("@openai/codex".to_string())));
+    }
+}
@@ -0,0 +1,153 @@
+use serde::Deserialize;
+
+/// How to find the version in a tool's version command output.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum VersionParser {
+    /// Use the whole trimmed output.
+    #[default]
+    Output,
+    /// Use the first line, optionally stripping a prefix and cutting at a marker.
+    FirstLine {
+        #[serde(default)]
+        strip_prefix: Option<String>,
+        #[serde(default)]
+        cut_at: Option<String>,
+    },
+    /// Use the last line that looks like a bare version, e.g. `v0.22.3`.
+    LastVersionLine,
+    /// Use the value following `label`, plus any labeled sub-components.
+    Labeled {
+        label: String,
+        #[serde(default)]
+        components: Vec<ComponentLabel>,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ComponentLabel {
+    pub name: String,
+    pub label: String,
+}
+
+/// The version text found in the output, plus labeled sub-components such as Cline Core.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedVersion {
+    pub version: String,
+    pub components: Vec<Component>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    pub name: String,
+    pub version: String,
+}
+
+impl ParsedVersion {
+    fn new(version: impl Into<String>) -> Self {
+        Self {
+            version: version.into(),
+            components: Vec::new(),
+        }
+    }
+}
+
+impl VersionParser {
+    pub fn parse(&self, output: &str) -> Option<ParsedVersion> {
+        match self {
+            VersionParser::Output => non_empty(output.trim()),
+            VersionParser::FirstLine {
+                strip_prefix,
+                cut_at,
+            } => {
+                let mut line = output.lines().next()?.trim();
+                if let Some(prefix) = strip_prefix {
+                    line = line.trim_start_matches(prefix.as_str()).trim();
+                }
+                if let Some(marker) = cut_at {
+                    line = line.split(marker.as_str()).next().unwrap_or(line).trim();
+                }
+                non_empty(line)
+            }
+            VersionParser::LastVersionLine => output
+                .lines()
+                .filter_map(|line| {
+                    let candidate = line.trim().trim_start_matches('v');
+                    let is_version = !candidate.is_empty()
+                        && candidate.chars().all(|c| c.is_ascii_digit() || c == '.');
+                    is_version.then(|| ParsedVersion::new(candidate))
+                })
+                .next_back(),
+            VersionParser::Labeled { label, components } => {
+                let mut parsed = ParsedVersion::new(labeled_value(output, label)?);
+                parsed.components = components
+                    .iter()
+                    .filter_map(|component| {
+                        labeled_value(output, &component.label).map(|version| Component {
+                            name: component.name.clone(),
+                            version,
+                        })
+                    })
+                    .collect();
+                Some(parsed)
+            }
+        }
+    }
+}
+
+fn labeled_value(output: &str, label: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let (_, rest) = line.split_once(label)?;
+        rest.split_whitespace().next().map(str::to_string)
+    })
+}
+
+fn non_empty(value: &str) -> Option<ParsedVersion> {
+    (!value.is_empty()).then(|| ParsedVersion::new(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Component, ComponentLabel, ParsedVersion, VersionParser};
+
+    #[test]
+    fn it_cuts_first_line_at_marker() {
+        let parser = VersionParser::FirstLine {
+            strip_prefix: None,
+            cut_at: Some(" (released".to_string()),
+        };
+        let parsed = parser
+            .parse("0.0.1762 (released 2025-11-02)\nextra")
+            .unwrap();
+        assert_eq!(parsed.version, "0.0.1762");
+    }
+
+    #[test]
+    fn it_picks_last_version_line() {
+        let parser = VersionParser::LastVersionLine;
+        let parsed = parser.parse("Factory Droid\nv0.22.3\n").unwrap();
+        assert_eq!(parsed.version, "0.22.3");
+    }
+
+    #[test]
+    fn it_parses_labeled_components() {
+        let parser = VersionParser::Labeled {
+            label: "Cline CLI Version:".to_string(),
+            components: vec![ComponentLabel {
+                name: "Core".to_string(),
+                label: "Cline Core Version:".to_string(),
+            }],
+        };
+        let output = "Cline CLI Version: 1.0.5\nCline Core Version: 3.35.0\n";
+        assert_eq!(
+            parser.parse(output),
+            Some(ParsedVersion {
+                version: "1.0.5".to_string(),
+                components: vec![Component {
+                    name: "Core".to_string(),
+                    version: "3.35.0".to_string(),
+                }],
+            })
+        );
+    }
+}
